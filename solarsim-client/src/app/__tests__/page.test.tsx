@@ -1,23 +1,20 @@
 import { render, screen } from "@testing-library/react";
 import Home from "../page";
-import { usePolling } from "../hooks/usePolling";
+import { useSimulationFields } from "../hooks/useSimulationFields";
 import {
   mockSimulationReadOnlyField,
   mockSimulationSliderField,
   networkError,
 } from "../__mocks__/constants";
 
-jest.mock("../hooks/usePolling");
+jest.mock("../hooks/useSimulationFields");
 
 describe("Home", () => {
   it("renders server unavailable error", () => {
-    (usePolling as jest.Mock).mockReturnValue({
-      data: networkError,
-      loading: false,
-      error: new Error(""),
-      pause: () => {},
-      resume: () => {},
-    });
+    (useSimulationFields as jest.Mock).mockReturnValue([
+      networkError,
+      () => {},
+    ]);
 
     render(<Home />);
 
@@ -25,16 +22,13 @@ describe("Home", () => {
   });
 
   it("renders simulation fields", () => {
-    (usePolling as jest.Mock).mockReturnValue({
-      data: {
+    (useSimulationFields as jest.Mock).mockReturnValue([
+      {
         slider_field: mockSimulationSliderField,
         read_only_field: mockSimulationReadOnlyField,
       },
-      loading: false,
-      error: null,
-      pause: () => {},
-      resume: () => {},
-    });
+      () => {},
+    ]);
 
     render(<Home />);
 
@@ -44,16 +38,13 @@ describe("Home", () => {
   });
 
   it("renders Home page unchanged", () => {
-    (usePolling as jest.Mock).mockReturnValue({
-      data: {
+    (useSimulationFields as jest.Mock).mockReturnValue([
+      {
         slider_field: mockSimulationSliderField,
         read_only_field: mockSimulationReadOnlyField,
       },
-      loading: false,
-      error: null,
-      pause: () => {},
-      resume: () => {},
-    });
+      () => {},
+    ]);
 
     const { container } = render(<Home />);
 
@@ -1,6 +1,24 @@
 import type { JSONValue } from "next/dist/server/config-shared";
 
-type CustomBRPMethod = "simulation.update_field";
+type CustomBRPMethod =
+  | "simulation.update_field"
+  | "simulation.update_fields"
+  | "simulation.pause"
+  | "simulation.resume"
+  | "simulation.step"
+  | "simulation.set_speed"
+  | "simulation.set_clock"
+  | "simulation.save_scenario"
+  | "simulation.load_scenario"
+  | "simulation.create_session"
+  | "simulation.list_sessions"
+  | "simulation.destroy_session"
+  | "simulation.history"
+  | "simulation.rewind"
+  | "simulation.describe"
+  | "simulation.get_state"
+  | "simulation.get_state+watch"
+  | "simulation.get_history";
 
 type BRPMethod =
   | "world.get_components"
@@ -67,6 +85,57 @@ export interface BRPSimulationUpdateFieldRequestBody
   params: {
     field_name: string;
     value: number;
+    clamp?: boolean;
+    override_read_only?: boolean;
+    session?: string;
+  };
+}
+
+export interface BRPSimulationUpdateFieldsRequestBody
+  extends BRPCommonRequestBody {
+  method: "simulation.update_fields";
+  params: {
+    fields: Record<string, number>;
+    clamp?: boolean;
+    override_read_only?: boolean;
+    session?: string;
+  };
+}
+
+export interface BRPSimulationDescribeRequestBody
+  extends BRPCommonRequestBody {
+  method: "simulation.describe";
+  params?: {
+    session?: string;
+  };
+}
+
+export interface BRPSimulationGetStateRequestBody
+  extends BRPCommonRequestBody {
+  method: "simulation.get_state" | "simulation.get_state+watch";
+  params?: {
+    /** Fields to include, every field when absent */
+    fields?: string[];
+    /** Smallest change of a field that is streamed, only used when watching */
+    deadband?: number;
+    /** Most updates streamed per second, only used when watching */
+    rate?: number;
+    session?: string;
+  };
+}
+
+export interface BRPSimulationGetHistoryRequestBody
+  extends BRPCommonRequestBody {
+  method: "simulation.get_history";
+  params: {
+    fields: string[];
+    /** Earliest sample, in simulated seconds since the start of the simulation */
+    start?: number;
+    /** Latest sample, in simulated seconds since the start of the simulation */
+    end?: number;
+    /** Most samples per field, at least 3 */
+    max_points?: number;
+    session?: string;
   };
 }
 
@@ -74,7 +143,11 @@ export type BRPRequestBody =
   | BRPGetComponentsRequestBody
   | BRPGetResourcesRequestBody
   | BRPMutateResourcesRequestBody
-  | BRPSimulationUpdateFieldRequestBody;
+  | BRPSimulationUpdateFieldRequestBody
+  | BRPSimulationUpdateFieldsRequestBody
+  | BRPSimulationDescribeRequestBody
+  | BRPSimulationGetStateRequestBody
+  | BRPSimulationGetHistoryRequestBody;
 
 // Responses
 
@@ -103,8 +176,88 @@ export interface BRPMutateResourcesResponse extends BRPCommonBody {
   result: null;
 }
 
+/** Error codes of the `simulation.*` methods, the error `data` holds the details. */
+export const SimulationErrorCode = {
+  UnknownField: -23601,
+  ReadOnlyField: -23602,
+  OutOfRange: -23603,
+  InvalidState: -23604,
+  SessionNotFound: -23605,
+  Unauthorized: -23606,
+  Forbidden: -23607,
+  TooManySessions: -23608,
+  ScenarioNotFound: -23609,
+  InvalidScenario: -23610,
+} as const;
+
+export interface SimulationFieldUpdate {
+  field: string;
+  old_value: number;
+  new_value: number;
+  clamped: boolean;
+}
+
 export interface BRPSimulationUpdateFieldResponse extends BRPCommonBody {
-  result: string;
+  result: SimulationFieldUpdate;
+}
+
+export interface BRPSimulationUpdateFieldsResponse extends BRPCommonBody {
+  result: SimulationFieldUpdate[];
+}
+
+export type SimulationFieldGroup =
+  | "site"
+  | "weather"
+  | "collector"
+  | "piping"
+  | "tank"
+  | "load";
+
+/** A `SimulationConfig` field, as described by `simulation.describe`. */
+export interface SimulationFieldDescription {
+  name: string;
+  label: string;
+  /** Unit symbol, empty for dimensionless values */
+  unit: string;
+  description: string;
+  group: SimulationFieldGroup;
+  step: number;
+  /** Number of decimal places */
+  precision: number;
+  value: number;
+  min: number;
+  max: number;
+  kind: "Slider" | "ReadOnly";
+}
+
+export interface BRPSimulationDescribeResponse extends BRPCommonBody {
+  result: SimulationFieldDescription[];
+}
+
+/**
+ * State of the simulation. When watching, `fields` only holds the fields that changed since
+ * the previous update.
+ */
+export interface SimulationState {
+  step: number;
+  /** Local date and time of the simulation */
+  time: string;
+  fields: Record<string, number>;
+}
+
+export interface BRPSimulationGetStateResponse extends BRPCommonBody {
+  result: SimulationState;
+}
+
+export interface BRPSimulationGetHistoryResponse extends BRPCommonBody {
+  result: {
+    /** Local date and time the simulation started at, which sample times count from */
+    start: string;
+    /** Simulated seconds between two samples */
+    interval: number;
+    /** `[time, value]` samples of every requested field, by field name */
+    fields: Record<string, [number, number][]>;
+  };
 }
 
 interface BRPRequestResponseMap {
@@ -127,6 +280,23 @@ interface BRPRequestResponseMap {
   "registry.schema": never;
   "rpc.discover": never;
   "simulation.update_field": BRPSimulationUpdateFieldResponse;
+  "simulation.update_fields": BRPSimulationUpdateFieldsResponse;
+  "simulation.pause": never;
+  "simulation.resume": never;
+  "simulation.step": never;
+  "simulation.set_speed": never;
+  "simulation.set_clock": never;
+  "simulation.save_scenario": never;
+  "simulation.load_scenario": never;
+  "simulation.create_session": never;
+  "simulation.list_sessions": never;
+  "simulation.destroy_session": never;
+  "simulation.history": never;
+  "simulation.rewind": never;
+  "simulation.describe": BRPSimulationDescribeResponse;
+  "simulation.get_state": BRPSimulationGetStateResponse;
+  "simulation.get_state+watch": BRPSimulationGetStateResponse;
+  "simulation.get_history": BRPSimulationGetHistoryResponse;
 }
 
 export type BRPResponseFor<R extends BRPRequestBody> = R extends {
@@ -0,0 +1,98 @@
+import { renderHook, act } from "@testing-library/react";
+import { useSimulationFields } from "../useSimulationFields";
+import { brpRequest, brpWatch } from "../../requests";
+
+jest.mock("../../requests");
+
+const description = {
+  ok: true,
+  status: 200,
+  data: {
+    id: 0,
+    jsonrpc: "2.0",
+    result: [
+      { name: "panel_area", kind: "Slider", min: 1, max: 3, value: 2 },
+      {
+        name: "tank_average_temp",
+        kind: "ReadOnly",
+        min: 10,
+        max: 60,
+        value: 25,
+      },
+    ],
+  },
+};
+
+describe("useSimulationFields", () => {
+  let sendState: (fields: Record<string, number>) => void;
+
+  beforeEach(() => {
+    jest.clearAllMocks();
+    (brpRequest as jest.Mock).mockResolvedValue(description);
+    (brpWatch as jest.Mock).mockImplementation((_request, onResponse) => {
+      sendState = (fields) =>
+        onResponse({
+          id: 0,
+          jsonrpc: "2.0",
+          result: { step: 1, time: "", fields },
+        });
+
+      // The stream stays open.
+      return new Promise(() => {});
+    });
+  });
+
+  it("describes the fields once, then applies the watched state", async () => {
+    const userInteracting = { current: false };
+    const { result } = renderHook(() => useSimulationFields(userInteracting));
+
+    await act(async () => {
+      await Promise.resolve();
+    });
+
+    act(() => {
+      sendState({ panel_area: 2.5, tank_average_temp: 30 });
+    });
+
+    expect(brpRequest).toHaveBeenCalledTimes(1);
+    expect(result.current[0]).toEqual({
+      panel_area: { kind: "Slider", min: 1, max: 3, value: 2.5 },
+      tank_average_temp: { kind: "ReadOnly", min: 10, max: 60, value: 30 },
+    });
+  });
+
+  it("keeps slider values while the user drags them", async () => {
+    const userInteracting = { current: true };
+    const { result } = renderHook(() => useSimulationFields(userInteracting));
+
+    await act(async () => {
+      await Promise.resolve();
+    });
+
+    act(() => {
+      sendState({ panel_area: 2.5, tank_average_temp: 30 });
+    });
+
+    expect(result.current[0]).toMatchObject({
+      panel_area: { value: 2 },
+      tank_average_temp: { value: 30 },
+    });
+  });
+
+  it("reports an unavailable server", async () => {
+    (brpRequest as jest.Mock).mockResolvedValue({
+      ok: false,
+      error: { type: "NETWORK_ERROR", message: "" },
+    });
+
+    const userInteracting = { current: false };
+    const { result } = renderHook(() => useSimulationFields(userInteracting));
+
+    await act(async () => {
+      await Promise.resolve();
+    });
+
+    expect(result.current[0]).toEqual(Error("Server is unavailable :("));
+    expect(brpWatch).not.toHaveBeenCalled();
+  });
+});
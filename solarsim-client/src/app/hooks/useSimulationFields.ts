@@ -0,0 +1,103 @@
+import {
+  useEffect,
+  useState,
+  type Dispatch,
+  type RefObject,
+  type SetStateAction,
+} from "react";
+import { brpRequest, brpWatch } from "../requests";
+import { displayFetchErrorType } from "../utils/fetch";
+
+type SimulationFields = Record<string, SimulationField> | Error | null;
+
+/**
+ * Simulation fields, described once by `simulation.describe` and then kept up to date by
+ * `simulation.get_state+watch`.
+ *
+ * While the user drags a slider, only read-only fields are updated.
+ */
+export function useSimulationFields(
+  userInteracting: RefObject<boolean>
+): [SimulationFields, Dispatch<SetStateAction<SimulationFields>>] {
+  const [simulationFields, setSimulationFields] =
+    useState<SimulationFields>(null);
+
+  useEffect(() => {
+    const controller = new AbortController();
+
+    const run = async () => {
+      const body = await brpRequest({
+        id: 0,
+        jsonrpc: "2.0",
+        method: "simulation.describe",
+      });
+
+      if (controller.signal.aborted) return;
+
+      if (body.ok === false) {
+        setSimulationFields(Error(displayFetchErrorType(body.error.type)));
+        return;
+      }
+
+      setSimulationFields(
+        Object.fromEntries(
+          body.data.result.map(({ name, kind, min, max, value }) => [
+            name,
+            { kind, min, max, value },
+          ])
+        )
+      );
+
+      const result = await brpWatch(
+        {
+          id: 0,
+          jsonrpc: "2.0",
+          method: "simulation.get_state+watch",
+        },
+        (response) => {
+          if (!("result" in response)) return;
+
+          setSimulationFields((prev) => {
+            if (prev === null || Error.isError(prev)) return prev;
+
+            const fields = { ...prev };
+
+            for (const [name, value] of Object.entries(
+              response.result.fields
+            )) {
+              const field = fields[name];
+
+              if (
+                field &&
+                (!userInteracting.current || field.kind === "ReadOnly")
+              ) {
+                fields[name] = { ...field, value };
+              }
+            }
+
+            return fields;
+          });
+        },
+        controller.signal
+      );
+
+      if (controller.signal.aborted) return;
+
+      // The Server only ends the stream when it shuts down.
+      setSimulationFields(
+        Error(
+          displayFetchErrorType(result.ok ? "NETWORK_ERROR" : result.error.type)
+        )
+      );
+    };
+
+    run().catch((err) => {
+      console.error(err);
+      setSimulationFields(Error("Unknown error :("));
+    });
+
+    return () => controller.abort();
+  }, [userInteracting]);
+
+  return [simulationFields, setSimulationFields];
+}
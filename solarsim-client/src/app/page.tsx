@@ -1,49 +1,15 @@
 "use client";
 
-import { brpRequest } from "./requests";
-import { displayFetchErrorType } from "./utils/fetch";
 import { ReadOnlyFields } from "./components/ReadOnlyFields";
 import { SliderFields } from "./components/SliderFields";
 import { Container, Flex, Section, Text } from "@radix-ui/themes";
-import { useEffect, useRef, useState } from "react";
-import { usePolling } from "./hooks/usePolling";
+import { useRef } from "react";
+import { useSimulationFields } from "./hooks/useSimulationFields";
 
 export default function Home() {
-  const { data } = usePolling(() =>
-    brpRequest({
-      id: 0,
-      jsonrpc: "2.0",
-      method: "world.get_resources",
-      params: {
-        resource:
-          "solarsim_server::simulation::simulation_config::SimulationConfig",
-      },
-    })
-      .then((body) => {
-        if (body.ok === false) {
-          return Error(displayFetchErrorType(body.error.type));
-        } else {
-          return body.data.result.value as Record<string, SimulationField>;
-        }
-      })
-      .catch((err) => {
-        console.error(err);
-        return Error("Unknown error :(");
-      })
-  );
-
-  const [simulationFields, setSimulationFields] = useState<
-    Record<string, SimulationField> | Error | null
-  >({});
   const userInteracting = useRef(false);
-
-  // When polling updates come in, apply only if user isn't dragging
-  useEffect(() => {
-    if (!userInteracting.current) {
-      // eslint-disable-next-line react-hooks/set-state-in-effect
-      setSimulationFields(data);
-    }
-  }, [data]);
+  const [simulationFields, setSimulationFields] =
+    useSimulationFields(userInteracting);
 
   return (
     <Section>
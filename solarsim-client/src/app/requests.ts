@@ -3,9 +3,13 @@ import type { BRPRequestBody, BRPResponseFor } from "./brp";
 
 const host = process.env.NEXT_PUBLIC_SOLARSIM_SERVER_HOST;
 const port = process.env.NEXT_PUBLIC_SOLARSIM_SERVER_PORT;
+const token = process.env.NEXT_PUBLIC_SOLARSIM_SERVER_TOKEN;
 const url = URL.parse(`${host}:${port}`) ?? `${host}:${port}`;
 const requestInit: RequestInit = {
-  headers: { "Content-Type": "application/json" },
+  headers: {
+    "Content-Type": "application/json",
+    ...(token ? { Authorization: `Bearer ${token}` } : {}),
+  },
   method: "post",
 };
 const timeoutMs = 10_000;
@@ -71,3 +75,89 @@ export async function brpRequest<R extends BRPRequestBody>(
     };
   }
 }
+
+/**
+ * Perform a watching Bevy Remote Protocol request (a `+watch` method) against the Server,
+ * calling `onResponse` with every response the Server streams, until `signal` is aborted.
+ *
+ * Resolves once the stream ends, with an error if it could not be opened or broke off.
+ * */
+export async function brpWatch<R extends BRPRequestBody>(
+  request: R,
+  onResponse: (response: BRPResponseFor<R>) => void,
+  signal: AbortSignal
+): Promise<FetchResult<null>> {
+  try {
+    const response = await fetch(url, {
+      ...requestInit,
+      body: JSON.stringify(request),
+      signal,
+    });
+
+    if (!response.ok || response.body === null) {
+      const text = await response.text().catch(() => "");
+
+      return {
+        ok: false,
+        error: {
+          type: "BAD_STATUS",
+          message: `HTTP error ${response.status}: ${response.statusText}`,
+          status: response.status,
+          details: text,
+        },
+      };
+    }
+
+    const reader = response.body.pipeThrough(new TextDecoderStream()).getReader();
+    let buffer = "";
+
+    for (;;) {
+      const { done, value } = await reader.read();
+
+      if (done) {
+        return { ok: true, status: response.status, data: null };
+      }
+
+      // Server-sent events are separated by a blank line, the last one may be incomplete.
+      const events = (buffer + value).split("\n\n");
+
+      buffer = events.pop() ?? "";
+
+      for (const event of events) {
+        const data = event
+          .split("\n")
+          .filter((line) => line.startsWith("data:"))
+          .map((line) => line.slice("data:".length).trimStart())
+          .join("\n");
+
+        if (data) {
+          onResponse(JSON.parse(data));
+        }
+      }
+    }
+  } catch (error) {
+    if (signal.aborted) {
+      return { ok: true, status: 200, data: null };
+    }
+
+    if (error instanceof SyntaxError) {
+      return {
+        ok: false,
+        error: {
+          type: "INVALID_CONTENT",
+          message: error.message,
+          details: error,
+        },
+      };
+    }
+
+    return {
+      ok: false,
+      error: {
+        type: "NETWORK_ERROR",
+        message: (error as Error)?.message ?? "Network request failed",
+        details: error,
+      },
+    };
+  }
+}
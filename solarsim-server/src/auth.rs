@@ -0,0 +1,175 @@
+use std::{collections::HashSet, fs, path::Path};
+
+use serde::Deserialize;
+
+use crate::cli::ConfigError;
+
+/// What a client may do through BRP, each role can do everything the previous ones can.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Read the state, history and metadata of the simulations
+    Viewer,
+    /// Change fields, run, pause, rewind and manage scenarios and sessions
+    Operator,
+    /// Reach the generic `world.*` and `registry.*` methods and set read-only fields
+    Admin,
+}
+
+/// A token clients authenticate with, as an `Authorization: Bearer <token>` header.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AccessToken {
+    /// Who the token was given to, to tell the tokens apart in the logs
+    pub name: String,
+    pub token: String,
+    pub role: Role,
+}
+
+/// Tokens accepted by the BRP server, loaded from the `--tokens` file.
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct AccessTokens {
+    #[serde(default)]
+    tokens: Vec<AccessToken>,
+}
+
+/// Methods only reading the simulations, open to viewers.
+const VIEWER_METHODS: &[&str] = &[
+    "rpc.discover",
+    "simulation.describe",
+    "simulation.get_history",
+    "simulation.get_state",
+    "simulation.get_state+watch",
+    "simulation.history",
+    "simulation.list_sessions",
+];
+
+impl AccessTokens {
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let toml = fs::read_to_string(path).map_err(|error| ConfigError::Io {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        let tokens = toml::from_str::<Self>(&toml).map_err(|error| ConfigError::Parse {
+            path: path.to_path_buf(),
+            error,
+        })?;
+
+        tokens.validate(path)?;
+
+        Ok(tokens)
+    }
+
+    fn validate(&self, path: &Path) -> Result<(), ConfigError> {
+        let invalid =
+            |message: String| ConfigError::Invalid(format!("{}: {message}", path.display()));
+        let mut tokens = HashSet::new();
+
+        for token in &self.tokens {
+            if token.token.trim().is_empty() {
+                return Err(invalid(format!("token of {} is empty", token.name)));
+            }
+
+            if !tokens.insert(token.token.as_str()) {
+                return Err(invalid(format!("token of {} is given twice", token.name)));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The token matching `token`, if any.
+    pub fn find(&self, token: &str) -> Option<&AccessToken> {
+        // Every token is compared in full, so the time taken doesn't tell how close a guess was.
+        self.tokens.iter().fold(None, |found, candidate| {
+            match constant_time_eq(candidate.token.as_bytes(), token.as_bytes()) {
+                true => Some(candidate),
+                false => found,
+            }
+        })
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Least role allowed to call `method` with `params`.
+///
+/// The `simulation.*` methods not listed as reading need an operator, every other method,
+/// including the built-in ones added by later Bevy versions, an admin. Setting read-only fields,
+/// with the `override_read_only` flag of the field updates, also needs an admin.
+pub fn required_role(method: &str, params: Option<&serde_json::Value>) -> Role {
+    let overrides_read_only = params
+        .and_then(|params| params.get("override_read_only"))
+        .is_some_and(|flag| *flag == serde_json::Value::Bool(true));
+
+    match method {
+        _ if overrides_read_only => Role::Admin,
+        method if VIEWER_METHODS.contains(&method) => Role::Viewer,
+        method if method.starts_with("simulation.") => Role::Operator,
+        _ => Role::Admin,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generic_methods_and_overrides_are_reserved_to_admins() {
+        let update = serde_json::json!({ "field_name": "panel_area", "value": 3.0 });
+        let override_read_only = serde_json::json!({
+            "field_name": "tank_average_temp",
+            "value": 60.0,
+            "override_read_only": true,
+        });
+
+        assert_eq!(required_role("simulation.get_state", None), Role::Viewer);
+        assert_eq!(
+            required_role("simulation.update_field", Some(&update)),
+            Role::Operator
+        );
+        assert_eq!(
+            required_role("simulation.update_field", Some(&override_read_only)),
+            Role::Admin
+        );
+        assert_eq!(required_role("simulation.set_speed", None), Role::Operator);
+        assert_eq!(required_role("simulation.set_clock", None), Role::Operator);
+        assert_eq!(required_role("world.despawn_entity", None), Role::Admin);
+        assert_eq!(required_role("world.get_resources", None), Role::Admin);
+        assert!(Role::Admin > Role::Operator && Role::Operator > Role::Viewer);
+    }
+
+    #[test]
+    fn tokens_are_found_and_validated() {
+        let tokens = toml::from_str::<AccessTokens>(
+            r#"
+            [[tokens]]
+            name = "dashboard"
+            token = "abc"
+            role = "viewer"
+
+            [[tokens]]
+            name = "ops"
+            token = "abd"
+            role = "operator"
+            "#,
+        )
+        .unwrap();
+
+        assert!(tokens.validate(Path::new("tokens.toml")).is_ok());
+        assert_eq!(
+            tokens.find("abd").map(|token| token.role),
+            Some(Role::Operator)
+        );
+        assert!(tokens.find("ab").is_none());
+
+        let duplicated = AccessTokens {
+            tokens: vec![tokens.tokens[0].clone(), tokens.tokens[0].clone()],
+        };
+
+        assert!(duplicated.validate(Path::new("tokens.toml")).is_err());
+    }
+}
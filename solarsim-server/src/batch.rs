@@ -0,0 +1,236 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::Path,
+    time::Instant,
+};
+
+use bevy::prelude::*;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    cli::{BatchArgs, BatchFormat},
+    simulation::{
+        InputRecording, SimulationClock, SimulationConfig, SimulationRunner, SimulationStats,
+    },
+};
+
+#[derive(Debug)]
+pub enum BatchError {
+    /// The results could not be written.
+    Output(io::Error),
+}
+
+impl core::fmt::Display for BatchError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Output(error) => write!(f, "Unable to write results: {error}"),
+        }
+    }
+}
+
+impl core::error::Error for BatchError {}
+
+/// State of the simulation at a point in simulated time.
+#[derive(Serialize)]
+struct BatchSample {
+    /// Local date and time of the simulation clock
+    time: String,
+    elapsed_secs: f64,
+    /// Value of every `SimulationConfig` field
+    fields: Map<String, Value>,
+    stats: BatchStats,
+}
+
+/// Energy totals accumulated up to a sample.
+#[derive(Serialize, Clone)]
+pub struct BatchStats {
+    #[serde(flatten)]
+    totals: SimulationStats,
+    solar_fraction: f64,
+}
+
+#[derive(Serialize)]
+struct BatchResults {
+    samples: Vec<BatchSample>,
+    stats: BatchStats,
+}
+
+impl BatchSample {
+    fn new(world: &World) -> Self {
+        let clock = world.resource::<SimulationClock>();
+        let stats = world.resource::<SimulationStats>();
+        // Round-tripping through text keeps the shortest representation of the `f32` values,
+        // converting them to a `Value` directly would print them with `f64` noise.
+        let fields = match serde_json::to_string(world.resource::<SimulationConfig>())
+            .and_then(|json| serde_json::from_str::<Value>(&json))
+        {
+            Ok(Value::Object(fields)) => fields
+                .into_iter()
+                .map(|(name, field)| (name, field["value"].clone()))
+                .collect(),
+            _ => Map::default(),
+        };
+
+        Self {
+            time: clock.now().to_string(),
+            elapsed_secs: clock.elapsed_secs,
+            fields,
+            stats: BatchStats::new(stats),
+        }
+    }
+}
+
+impl BatchStats {
+    pub fn new(stats: &SimulationStats) -> Self {
+        Self {
+            totals: stats.clone(),
+            solar_fraction: stats.solar_fraction(),
+        }
+    }
+
+    /// Every total and the solar fraction, by the name they are serialized with.
+    pub fn metrics(&self) -> BTreeMap<&'static str, f64> {
+        let totals = &self.totals;
+
+        BTreeMap::from([
+            ("aux_energy", totals.aux_energy),
+            ("heat_loss", totals.heat_loss),
+            ("load_demand", totals.load_demand),
+            ("overheating_hours", totals.overheating_hours),
+            ("shading_loss", totals.shading_loss),
+            ("solar_gain", totals.solar_gain),
+            ("solar_fraction", self.solar_fraction),
+        ])
+    }
+}
+
+/// Runs the simulation over the requested period and writes the sampled results.
+///
+/// When replaying, the recorded changes are applied before the steps they were made at.
+pub fn run(
+    mut runner: SimulationRunner,
+    args: &BatchArgs,
+    replay: Option<&InputRecording>,
+) -> Result<(), BatchError> {
+    let steps = match (args.duration, replay) {
+        (Some(duration), _) => runner.steps_for(duration),
+        // One more step, so the last change has an effect.
+        (None, Some(recording)) => recording.last_step() + 1,
+        (None, None) => 0,
+    };
+    let sample_every = runner.steps_for(args.interval).max(1);
+    let started_at = Instant::now();
+    let mut samples = vec![BatchSample::new(runner.world())];
+
+    for step in 1..=steps {
+        if let Some(recording) = replay {
+            let world = runner.app_mut().world_mut();
+
+            recording.apply_changes(step - 1, &mut world.resource_mut::<SimulationConfig>());
+        }
+
+        runner.step();
+
+        if step % sample_every == 0 || step == steps {
+            samples.push(BatchSample::new(runner.world()));
+        }
+    }
+
+    info!(
+        "Simulated {steps} steps of {:?} in {:.2?}",
+        runner.timestep(),
+        started_at.elapsed()
+    );
+
+    let results = BatchResults {
+        samples,
+        stats: BatchStats::new(runner.world().resource::<SimulationStats>()),
+    };
+    let output = match args.format {
+        BatchFormat::Csv => to_csv(&results.samples),
+        BatchFormat::Json => serde_json::to_string_pretty(&results).map_err(io::Error::other),
+    }
+    .map_err(BatchError::Output)?;
+
+    write_output(args.output.as_deref(), &output).map_err(BatchError::Output)
+}
+
+/// Writes results to the file at `path`, or to stdout when there is none.
+pub fn write_output(path: Option<&Path>, output: &str) -> io::Result<()> {
+    match path {
+        Some(path) => fs::write(path, output),
+        None => io::stdout().write_all(output.as_bytes()),
+    }
+}
+
+/// One row per sample, with the field values followed by the energy totals.
+fn to_csv(samples: &[BatchSample]) -> io::Result<String> {
+    let mut csv = String::default();
+
+    for (index, sample) in samples.iter().enumerate() {
+        let Value::Object(stats) = serde_json::to_value(&sample.stats)? else {
+            return Err(io::Error::other("stats are not a map"));
+        };
+
+        if index == 0 {
+            let header = ["time", "elapsed_secs"]
+                .into_iter()
+                .chain(sample.fields.keys().map(String::as_str))
+                .chain(stats.keys().map(String::as_str))
+                .collect::<Vec<_>>();
+
+            csv.push_str(&header.join(","));
+            csv.push('\n');
+        }
+
+        let row = [sample.time.clone(), sample.elapsed_secs.to_string()]
+            .into_iter()
+            .chain(sample.fields.values().map(Value::to_string))
+            .chain(stats.values().map(Value::to_string))
+            .collect::<Vec<_>>();
+
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    Ok(csv)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FIXED_TIMESTEP_SECS;
+
+    use super::*;
+
+    #[test]
+    fn csv_has_a_header_and_a_row_per_sample() {
+        let mut runner = SimulationRunner::new(FIXED_TIMESTEP_SECS);
+        let mut samples = vec![BatchSample::new(runner.world())];
+
+        runner.step();
+        samples.push(BatchSample::new(runner.world()));
+
+        let csv = to_csv(&samples).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        let columns = |line: &str| line.split(',').count();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("time,elapsed_secs,ambient_temp,"));
+        assert!(lines[0].ends_with(",solar_fraction,solar_gain"));
+        assert_eq!(columns(lines[0]), columns(lines[2]));
+        assert!(lines[2].starts_with("2025-06-21 06:00:00,0.5,"));
+    }
+
+    #[test]
+    fn metrics_are_named_like_the_serialized_stats() {
+        let stats = BatchStats::new(&SimulationStats::default());
+        let Ok(Value::Object(serialized)) = serde_json::to_value(&stats) else {
+            panic!("stats are not a map");
+        };
+
+        assert!(stats.metrics().into_keys().eq(serialized.keys()));
+    }
+}
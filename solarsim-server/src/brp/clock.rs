@@ -0,0 +1,96 @@
+use bevy::{
+    ecs::{system::In, world::World},
+    remote::{
+        BrpError, BrpResult,
+        error_codes::{INTERNAL_ERROR, INVALID_PARAMS, INVALID_REQUEST, PARSE_ERROR},
+    },
+};
+use serde::Deserialize;
+
+use super::{error_codes::INVALID_STATE, in_session};
+use crate::simulation::{CalendarDateTime, InputRecorder, SimulationClock};
+
+/// Range of the UTC offsets in use, measured in hours.
+const UTC_OFFSETS: core::ops::RangeInclusive<f32> = -12.0..=14.0;
+
+#[derive(Deserialize)]
+struct SetClockRequest {
+    /// Local date and time the simulation started at
+    pub start: CalendarDateTime,
+    /// Offset of the local time zone from UTC, measured in hours, unchanged when absent
+    #[serde(default)]
+    pub utc_offset: Option<f32>,
+    #[serde(default)]
+    pub session: Option<String>,
+}
+
+/// Moves the simulation to another date, keeping the time elapsed and steps run since the start.
+///
+/// Refused while recording, the recording would no longer match the run.
+pub fn simulation_set_clock(
+    In(params): In<Option<serde_json::Value>>,
+    world: &mut World,
+) -> BrpResult {
+    const METHOD: &str = "simulation.set_clock";
+
+    let Some(value) = params else {
+        return Err(BrpError {
+            code: INVALID_REQUEST,
+            data: None,
+            message: format!("{METHOD}: Request was empty"),
+        });
+    };
+    let Ok(request) = serde_json::from_value::<SetClockRequest>(value) else {
+        return Err(BrpError {
+            code: PARSE_ERROR,
+            data: None,
+            message: format!("{METHOD}: Unable to parse request"),
+        });
+    };
+
+    if let Err(message) = request.start.validate() {
+        return Err(BrpError {
+            code: INVALID_PARAMS,
+            data: None,
+            message: format!("{METHOD}: {message}"),
+        });
+    }
+
+    if let Some(utc_offset) = request.utc_offset
+        && !UTC_OFFSETS.contains(&utc_offset)
+    {
+        return Err(BrpError {
+            code: INVALID_PARAMS,
+            data: None,
+            message: format!(
+                "{METHOD}: utc_offset must be within {}..={}",
+                UTC_OFFSETS.start(),
+                UTC_OFFSETS.end()
+            ),
+        });
+    }
+
+    in_session(world, METHOD, request.session.as_deref(), |world| {
+        // Clock changes are not recorded. Sessions have no recorder.
+        if world.contains_resource::<InputRecorder>() {
+            return Err(BrpError {
+                code: INVALID_STATE,
+                data: None,
+                message: format!("{METHOD}: The clock can't be set while recording"),
+            });
+        }
+
+        let Some(mut clock) = world.get_resource_mut::<SimulationClock>() else {
+            return Err(BrpError {
+                code: INTERNAL_ERROR,
+                data: None,
+                message: format!("{METHOD}: SimulationClock resource not found"),
+            });
+        };
+
+        clock.start = request.start;
+        clock.utc_offset = request.utc_offset.unwrap_or(clock.utc_offset);
+
+        serde_json::to_value(&*clock).map_err(BrpError::internal)
+    })
+}
@@ -0,0 +1,55 @@
+use bevy::{
+    ecs::{system::In, world::World},
+    remote::{BrpError, BrpResult, error_codes::INTERNAL_ERROR},
+};
+use serde::Serialize;
+
+use super::{SessionParams, in_session, parse_optional_params};
+use crate::simulation::{FIELD_METADATA, FieldMetadata, SimulationConfig, SimulationFieldKind};
+
+/// Everything a client needs to build the form of a field.
+#[derive(Serialize)]
+struct FieldDescription {
+    #[serde(flatten)]
+    pub metadata: &'static FieldMetadata,
+    pub value: f32,
+    pub min: f32,
+    pub max: f32,
+    pub kind: SimulationFieldKind,
+}
+
+/// Describes every `SimulationConfig` field, in the order they are presented.
+pub fn simulation_describe(
+    In(params): In<Option<serde_json::Value>>,
+    world: &mut World,
+) -> BrpResult {
+    const METHOD: &str = "simulation.describe";
+
+    let request = parse_optional_params::<SessionParams>(METHOD, params)?;
+
+    in_session(world, METHOD, request.session.as_deref(), |world| {
+        let Some(cfg) = world.get_resource::<SimulationConfig>() else {
+            return Err(BrpError {
+                code: INTERNAL_ERROR,
+                data: None,
+                message: format!("{METHOD}: SimulationConfig resource not found"),
+            });
+        };
+        let fields = FIELD_METADATA
+            .iter()
+            .filter_map(|metadata| {
+                let field = cfg.field(metadata.name)?;
+
+                Some(FieldDescription {
+                    metadata,
+                    value: **field,
+                    min: field.min(),
+                    max: field.max(),
+                    kind: field.kind(),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::to_value(fields).map_err(BrpError::internal)
+    })
+}
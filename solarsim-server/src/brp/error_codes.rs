@@ -0,0 +1,22 @@
+/// The field does not exist, `data` holds the `field`.
+pub const UNKNOWN_FIELD: i16 = -23601;
+/// The field is only set by the simulation, `data` holds the `field`.
+pub const READ_ONLY_FIELD: i16 = -23602;
+/// The value is outside of the range of the field, `data` holds the `field`, `value`, `min` and
+/// `max`.
+pub const OUT_OF_RANGE: i16 = -23603;
+/// The simulation is not in a state that allows the request.
+pub const INVALID_STATE: i16 = -23604;
+/// The session does not exist, `data` holds the `session`.
+pub const SESSION_NOT_FOUND: i16 = -23605;
+/// The request has no access token, or one that is not configured.
+pub const UNAUTHORIZED: i16 = -23606;
+/// The role of the access token does not allow the method.
+pub const FORBIDDEN: i16 = -23607;
+/// The most sessions allowed are already running, `data` holds the `max_sessions`.
+pub const TOO_MANY_SESSIONS: i16 = -23608;
+/// There is no scenario with the requested name, `data` holds the `name`.
+pub const SCENARIO_NOT_FOUND: i16 = -23609;
+/// The scenario file can't be loaded, `data` holds the `name` and, when a field is invalid, the
+/// `field`.
+pub const INVALID_SCENARIO: i16 = -23610;
@@ -0,0 +1,110 @@
+use bevy::{
+    ecs::{system::In, world::World},
+    remote::{
+        BrpError, BrpResult,
+        error_codes::{INTERNAL_ERROR, INVALID_REQUEST, PARSE_ERROR},
+    },
+};
+use serde::{Deserialize, Serialize};
+
+use super::{SessionParams, error_codes::INVALID_STATE, in_session, parse_optional_params};
+use crate::simulation::{InputRecorder, SimulationHistory, rewind};
+
+#[derive(Deserialize)]
+struct RewindRequest {
+    /// Step to rewind to, the newest entry recorded at or before it is restored
+    pub step: u64,
+    #[serde(default)]
+    pub session: Option<String>,
+}
+
+#[derive(Serialize)]
+struct RewindResponse {
+    /// Step of the restored entry
+    pub step: u64,
+    /// Local date and time of the restored entry
+    pub time: String,
+}
+
+pub fn simulation_history(
+    In(params): In<Option<serde_json::Value>>,
+    world: &mut World,
+) -> BrpResult {
+    const METHOD: &str = "simulation.history";
+
+    let request = parse_optional_params::<SessionParams>(METHOD, params)?;
+
+    in_session(world, METHOD, request.session.as_deref(), |world| {
+        let Some(history) = world.get_resource::<SimulationHistory>() else {
+            return Err(history_not_found(METHOD));
+        };
+
+        serde_json::to_value(history.summary()).map_err(BrpError::internal)
+    })
+}
+
+/// Restores an earlier state of the simulation, which continues from there when it runs again.
+///
+/// Refused while recording, the recording would no longer match the run.
+pub fn simulation_rewind(
+    In(params): In<Option<serde_json::Value>>,
+    world: &mut World,
+) -> BrpResult {
+    const METHOD: &str = "simulation.rewind";
+
+    let Some(value) = params else {
+        return Err(BrpError {
+            code: INVALID_REQUEST,
+            data: None,
+            message: format!("{METHOD}: Request was empty"),
+        });
+    };
+    let Ok(request) = serde_json::from_value::<RewindRequest>(value) else {
+        return Err(BrpError {
+            code: PARSE_ERROR,
+            data: None,
+            message: format!("{METHOD}: Unable to parse request"),
+        });
+    };
+
+    in_session(world, METHOD, request.session.as_deref(), |world| {
+        if !world.contains_resource::<SimulationHistory>() {
+            return Err(history_not_found(METHOD));
+        }
+
+        // The recording replays changes in step order, it has no way to go back. Sessions have no
+        // recorder.
+        if world.contains_resource::<InputRecorder>() {
+            return Err(BrpError {
+                code: INVALID_STATE,
+                data: None,
+                message: format!("{METHOD}: The simulation can't be rewound while recording"),
+            });
+        }
+
+        let Some(clock) = rewind(world, request.step) else {
+            return Err(BrpError {
+                code: INVALID_STATE,
+                data: Some(serde_json::json!({ "step": request.step })),
+                message: format!(
+                    "{METHOD}: No history entry at or before step {}",
+                    request.step
+                ),
+            });
+        };
+
+        serde_json::to_value(RewindResponse {
+            step: clock.steps,
+            time: clock.now().to_string(),
+        })
+        .map_err(BrpError::internal)
+    })
+}
+
+fn history_not_found(method: &str) -> BrpError {
+    BrpError {
+        code: INTERNAL_ERROR,
+        data: None,
+        message: format!("{method}: SimulationHistory resource not found"),
+    }
+}
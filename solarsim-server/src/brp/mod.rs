@@ -1,3 +1,28 @@
+mod clock;
+mod describe;
+/// Codes of the errors specific to the `simulation.*` methods, following the `-234xx` and
+/// `-235xx` codes of the built-in `world.*` methods.
+pub mod error_codes;
+mod history;
+mod resources;
+mod run_state;
+mod scenario;
+mod series;
+mod session;
+mod state;
 mod update_field;
 
+pub use clock::*;
+pub use describe::*;
+pub use history::*;
+pub use resources::*;
+pub use run_state::*;
+pub use scenario::*;
+pub use series::*;
+pub use session::*;
+pub use state::*;
 pub use update_field::*;
+
+/// Parameter the HTTP server adds to every watching request, holding an id unique to the request,
+/// so that the method can keep what it sent to each request apart.
+pub const STREAM_ID_PARAM: &str = "stream_id";
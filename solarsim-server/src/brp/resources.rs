@@ -0,0 +1,89 @@
+use bevy::{
+    ecs::{system::In, world::World},
+    reflect::TypePath,
+    remote::{
+        BrpError, BrpResult,
+        builtin_methods::{
+            process_remote_insert_resources_request, process_remote_mutate_resources_request,
+            process_remote_remove_resources_request,
+        },
+    },
+};
+
+use super::error_codes::INVALID_STATE;
+use crate::simulation::{ChangeSource, InputRecorder, SimulationConfig, record_config_changes};
+
+/// The built-in `world.insert_resources`, recording the changes it makes to the
+/// `SimulationConfig`.
+///
+/// Refused while recording for any other resource, the recording would no longer match the run.
+pub fn world_insert_resources(
+    In(params): In<Option<serde_json::Value>>,
+    world: &mut World,
+) -> BrpResult {
+    refuse_unrecorded_write(world, "world.insert_resources", params.as_ref())?;
+
+    record_config_changes(world, ChangeSource::WorldResources, |world| {
+        process_remote_insert_resources_request(In(params), world)
+    })
+}
+
+/// The built-in `world.mutate_resources`, recording the changes it makes to the
+/// `SimulationConfig`.
+///
+/// Refused while recording for any other resource, the recording would no longer match the run.
+pub fn world_mutate_resources(
+    In(params): In<Option<serde_json::Value>>,
+    world: &mut World,
+) -> BrpResult {
+    refuse_unrecorded_write(world, "world.mutate_resources", params.as_ref())?;
+
+    record_config_changes(world, ChangeSource::WorldResources, |world| {
+        process_remote_mutate_resources_request(In(params), world)
+    })
+}
+
+/// The built-in `world.remove_resources`, refused while recording.
+pub fn world_remove_resources(
+    In(params): In<Option<serde_json::Value>>,
+    world: &mut World,
+) -> BrpResult {
+    const METHOD: &str = "world.remove_resources";
+
+    if world.contains_resource::<InputRecorder>() {
+        return Err(BrpError {
+            code: INVALID_STATE,
+            data: None,
+            message: format!("{METHOD}: Resources can't be removed while recording"),
+        });
+    }
+
+    process_remote_remove_resources_request(In(params), world)
+}
+
+/// Only changes to the `SimulationConfig` are recorded, writing to any other resource would make
+/// the replay differ.
+fn refuse_unrecorded_write(
+    world: &World,
+    method: &str,
+    params: Option<&serde_json::Value>,
+) -> Result<(), BrpError> {
+    let resource = params
+        .and_then(|params| params.get("resource"))
+        .and_then(serde_json::Value::as_str);
+
+    if !world.contains_resource::<InputRecorder>()
+        || resource.is_none_or(|resource| resource == SimulationConfig::type_path())
+    {
+        return Ok(());
+    }
+
+    Err(BrpError {
+        code: INVALID_STATE,
+        data: None,
+        message: format!(
+            "{method}: Only {} can be changed while recording",
+            SimulationConfig::type_path()
+        ),
+    })
+}
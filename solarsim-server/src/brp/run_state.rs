@@ -0,0 +1,148 @@
+use bevy::{
+    ecs::{system::In, world::World},
+    remote::{
+        BrpError, BrpResult,
+        error_codes::{INTERNAL_ERROR, INVALID_PARAMS, INVALID_REQUEST, PARSE_ERROR},
+    },
+};
+use serde::Deserialize;
+
+use super::{SessionParams, in_session, parse_optional_params};
+use crate::simulation::{SimulationRunState, SimulationSpeed, SpeedTarget};
+
+#[derive(Deserialize)]
+struct StepSimulationRequest {
+    #[serde(default = "default_steps")]
+    pub steps: u32,
+    #[serde(default)]
+    pub session: Option<String>,
+}
+
+impl Default for StepSimulationRequest {
+    fn default() -> Self {
+        Self {
+            steps: default_steps(),
+            session: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SetSpeedRequest {
+    pub target: SpeedTarget,
+    #[serde(default)]
+    pub session: Option<String>,
+}
+
+fn default_steps() -> u32 {
+    1
+}
+
+fn update_run_state(
+    world: &mut World,
+    method: &str,
+    session: Option<&str>,
+    update: impl FnOnce(&mut SimulationRunState),
+) -> BrpResult {
+    in_session(world, method, session, |world| {
+        let Some(mut state) = world.get_resource_mut::<SimulationRunState>() else {
+            return Err(BrpError {
+                code: INTERNAL_ERROR,
+                data: None,
+                message: format!("{method}: SimulationRunState resource not found"),
+            });
+        };
+
+        update(&mut state);
+
+        serde_json::to_value(*state).map_err(BrpError::internal)
+    })
+}
+
+pub fn simulation_pause(In(params): In<Option<serde_json::Value>>, world: &mut World) -> BrpResult {
+    const METHOD: &str = "simulation.pause";
+
+    let request = parse_optional_params::<SessionParams>(METHOD, params)?;
+
+    update_run_state(world, METHOD, request.session.as_deref(), |state| {
+        *state = SimulationRunState::Paused;
+    })
+}
+
+pub fn simulation_resume(
+    In(params): In<Option<serde_json::Value>>,
+    world: &mut World,
+) -> BrpResult {
+    const METHOD: &str = "simulation.resume";
+
+    let request = parse_optional_params::<SessionParams>(METHOD, params)?;
+
+    update_run_state(world, METHOD, request.session.as_deref(), |state| {
+        *state = SimulationRunState::Running;
+    })
+}
+
+pub fn simulation_step(In(params): In<Option<serde_json::Value>>, world: &mut World) -> BrpResult {
+    const METHOD: &str = "simulation.step";
+
+    let request = parse_optional_params::<StepSimulationRequest>(METHOD, params)?;
+
+    update_run_state(world, METHOD, request.session.as_deref(), |state| {
+        state.step(request.steps);
+    })
+}
+
+/// Sets the speed of the main simulation, which sessions step along with.
+pub fn simulation_set_speed(
+    In(params): In<Option<serde_json::Value>>,
+    world: &mut World,
+) -> BrpResult {
+    const METHOD: &str = "simulation.set_speed";
+
+    let Some(value) = params else {
+        return Err(BrpError {
+            code: INVALID_REQUEST,
+            data: None,
+            message: format!("{METHOD}: Request was empty"),
+        });
+    };
+    let Ok(request) = serde_json::from_value::<SetSpeedRequest>(value) else {
+        return Err(BrpError {
+            code: PARSE_ERROR,
+            data: None,
+            message: format!("{METHOD}: Unable to parse request"),
+        });
+    };
+
+    if let SpeedTarget::Multiplier(multiplier) = request.target
+        && !(multiplier.is_finite() && multiplier > 0.0)
+    {
+        return Err(BrpError {
+            code: INVALID_PARAMS,
+            data: None,
+            message: format!("{METHOD}: Multiplier must be a positive finite number"),
+        });
+    }
+
+    if let Some(session) = request.session {
+        return Err(BrpError {
+            code: INVALID_PARAMS,
+            data: Some(serde_json::json!({ "session": session })),
+            message: format!(
+                "{METHOD}: Sessions run at the speed of the main simulation, set it without a session"
+            ),
+        });
+    }
+
+    let Some(mut speed) = world.get_resource_mut::<SimulationSpeed>() else {
+        return Err(BrpError {
+            code: INTERNAL_ERROR,
+            data: None,
+            message: format!("{METHOD}: SimulationSpeed resource not found"),
+        });
+    };
+
+    speed.target = request.target;
+
+    serde_json::to_value(*speed).map_err(BrpError::internal)
+}
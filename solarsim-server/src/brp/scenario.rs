@@ -0,0 +1,141 @@
+use bevy::{
+    ecs::{system::In, world::World},
+    remote::{
+        BrpError, BrpResult,
+        error_codes::{INTERNAL_ERROR, INVALID_PARAMS, INVALID_REQUEST, PARSE_ERROR},
+    },
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    error_codes::{INVALID_SCENARIO, SCENARIO_NOT_FOUND},
+    in_session,
+    update_field::config_not_found,
+};
+use crate::simulation::{
+    ActiveScenario, ChangeSource, Scenario, ScenarioError, SimulationConfig, record_config_changes,
+};
+
+#[derive(Deserialize)]
+struct ScenarioRequest {
+    pub name: String,
+    #[serde(default)]
+    pub session: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ScenarioResponse {
+    pub name: String,
+    pub path: String,
+}
+
+fn parse_request(
+    method: &str,
+    params: Option<serde_json::Value>,
+) -> Result<ScenarioRequest, BrpError> {
+    let Some(value) = params else {
+        return Err(BrpError {
+            code: INVALID_REQUEST,
+            data: None,
+            message: format!("{method}: Request was empty"),
+        });
+    };
+
+    serde_json::from_value::<ScenarioRequest>(value).map_err(|_| BrpError {
+        code: PARSE_ERROR,
+        data: None,
+        message: format!("{method}: Unable to parse request"),
+    })
+}
+
+/// Error of a method loading or saving the scenario `name`, only I/O failures other than a missing
+/// file are internal errors.
+pub(super) fn scenario_error(method: &str, name: &str, error: ScenarioError) -> BrpError {
+    let (code, data) = match &error {
+        ScenarioError::InvalidName(_) => (INVALID_PARAMS, serde_json::json!({ "name": name })),
+        ScenarioError::Io { error, .. } if error.kind() == std::io::ErrorKind::NotFound => {
+            (SCENARIO_NOT_FOUND, serde_json::json!({ "name": name }))
+        }
+        ScenarioError::Io { .. } => {
+            return BrpError {
+                code: INTERNAL_ERROR,
+                data: None,
+                message: format!("{method}: {error}"),
+            };
+        }
+        ScenarioError::InvalidField { field, .. } => (
+            INVALID_SCENARIO,
+            serde_json::json!({ "name": name, "field": field }),
+        ),
+        ScenarioError::Parse(_)
+        | ScenarioError::Invalid(_)
+        | ScenarioError::UnsupportedVersion(_) => {
+            (INVALID_SCENARIO, serde_json::json!({ "name": name }))
+        }
+    };
+
+    BrpError {
+        code,
+        data: Some(data),
+        message: format!("{method}: {error}"),
+    }
+}
+
+/// Saves the current `SimulationConfig` as a named scenario.
+pub fn simulation_save_scenario(
+    In(params): In<Option<serde_json::Value>>,
+    world: &mut World,
+) -> BrpResult {
+    const METHOD: &str = "simulation.save_scenario";
+
+    let request = parse_request(METHOD, params)?;
+
+    in_session(world, METHOD, request.session.as_deref(), |world| {
+        let Some(config) = world.get_resource::<SimulationConfig>() else {
+            return Err(config_not_found(METHOD));
+        };
+        let scenario = Scenario::new(request.name, config.clone());
+        let path = scenario
+            .save_named()
+            .map_err(|error| scenario_error(METHOD, &scenario.name, error))?;
+
+        world.insert_resource(ActiveScenario {
+            name: scenario.name.clone(),
+        });
+
+        serde_json::to_value(ScenarioResponse {
+            name: scenario.name,
+            path: path.display().to_string(),
+        })
+        .map_err(BrpError::internal)
+    })
+}
+
+/// Replaces the `SimulationConfig` with the one of a named scenario, recording the fields it
+/// changes.
+pub fn simulation_load_scenario(
+    In(params): In<Option<serde_json::Value>>,
+    world: &mut World,
+) -> BrpResult {
+    const METHOD: &str = "simulation.load_scenario";
+
+    let request = parse_request(METHOD, params)?;
+    let scenario_error = |error| scenario_error(METHOD, &request.name, error);
+    let scenario = Scenario::load_named(&request.name).map_err(scenario_error)?;
+    let path = Scenario::path_for(&request.name).map_err(scenario_error)?;
+
+    in_session(world, METHOD, request.session.as_deref(), |world| {
+        record_config_changes(world, ChangeSource::Scenario, |world| {
+            world.insert_resource(scenario.config);
+        });
+        world.insert_resource(ActiveScenario {
+            name: request.name.clone(),
+        });
+
+        serde_json::to_value(ScenarioResponse {
+            name: request.name,
+            path: path.display().to_string(),
+        })
+        .map_err(BrpError::internal)
+    })
+}
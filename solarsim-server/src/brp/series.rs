@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+
+use bevy::{
+    ecs::{system::In, world::World},
+    remote::{
+        BrpError, BrpResult,
+        error_codes::{INTERNAL_ERROR, INVALID_PARAMS, INVALID_REQUEST, PARSE_ERROR},
+    },
+};
+use serde::{Deserialize, Serialize};
+
+use super::{in_session, update_field::field_error};
+use crate::simulation::{FieldError, FieldSeries, SimulationClock};
+
+#[derive(Deserialize)]
+struct GetHistoryRequest {
+    pub fields: Vec<String>,
+    /// Earliest sample returned, in simulated seconds since the start of the simulation
+    #[serde(default)]
+    pub start: Option<f64>,
+    /// Latest sample returned, in simulated seconds since the start of the simulation
+    #[serde(default)]
+    pub end: Option<f64>,
+    /// Most samples returned per field, every sample when absent
+    #[serde(default)]
+    pub max_points: Option<usize>,
+    #[serde(default)]
+    pub session: Option<String>,
+}
+
+#[derive(Serialize)]
+struct GetHistoryResponse {
+    /// Local date and time the simulation started at, which the sample times count from
+    pub start: String,
+    /// Simulated time between two samples, measured in seconds
+    pub interval: f64,
+    /// `[time, value]` samples of every requested field, by field name
+    pub fields: BTreeMap<String, Vec<(f64, f32)>>,
+}
+
+/// Returns the time series of the requested fields, downsampled to `max_points` samples.
+pub fn simulation_get_history(
+    In(params): In<Option<serde_json::Value>>,
+    world: &mut World,
+) -> BrpResult {
+    const METHOD: &str = "simulation.get_history";
+
+    let Some(value) = params else {
+        return Err(BrpError {
+            code: INVALID_REQUEST,
+            data: None,
+            message: format!("{METHOD}: Request was empty"),
+        });
+    };
+    let Ok(request) = serde_json::from_value::<GetHistoryRequest>(value) else {
+        return Err(BrpError {
+            code: PARSE_ERROR,
+            data: None,
+            message: format!("{METHOD}: Unable to parse request"),
+        });
+    };
+
+    if request.max_points.is_some_and(|max_points| max_points < 3) {
+        return Err(BrpError {
+            code: INVALID_PARAMS,
+            data: None,
+            message: format!("{METHOD}: max_points must be at least 3"),
+        });
+    }
+
+    in_session(world, METHOD, request.session.as_deref(), |world| {
+        let Some(series) = world.get_resource::<FieldSeries>() else {
+            return Err(BrpError {
+                code: INTERNAL_ERROR,
+                data: None,
+                message: format!("{METHOD}: FieldSeries resource not found"),
+            });
+        };
+        let start = request.start.unwrap_or(f64::NEG_INFINITY);
+        let end = request.end.unwrap_or(f64::INFINITY);
+        let fields = request
+            .fields
+            .iter()
+            .map(|name| {
+                series
+                    .query(name, start, end, request.max_points)
+                    .map(|points| (name.clone(), points))
+                    .ok_or_else(|| field_error(METHOD, name, FieldError::UnknownField))
+            })
+            .collect::<Result<_, _>>()?;
+
+        serde_json::to_value(GetHistoryResponse {
+            start: world
+                .get_resource::<SimulationClock>()
+                .map_or_else(String::new, |clock| clock.start.to_string()),
+            interval: series.interval_secs(),
+            fields,
+        })
+        .map_err(BrpError::internal)
+    })
+}
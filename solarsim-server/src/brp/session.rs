@@ -0,0 +1,192 @@
+use bevy::{
+    ecs::{system::In, world::World},
+    remote::{
+        BrpError, BrpResult,
+        error_codes::{INTERNAL_ERROR, INVALID_REQUEST, PARSE_ERROR},
+    },
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    error_codes::{SESSION_NOT_FOUND, TOO_MANY_SESSIONS},
+    scenario::scenario_error,
+};
+use crate::{
+    sessions::SimulationSessions,
+    simulation::{FieldSeries, Scenario, SimulationHistory, SimulationSnapshot},
+};
+
+/// Optional `session` parameter taken by every `simulation.*` method.
+#[derive(Deserialize, Default)]
+pub(super) struct SessionParams {
+    #[serde(default)]
+    pub session: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct CreateSessionRequest {
+    /// Named scenario the session starts from, instead of the current main simulation
+    #[serde(default)]
+    pub scenario: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct DestroySessionRequest {
+    pub session: String,
+}
+
+#[derive(Serialize)]
+struct SessionResponse {
+    pub session: String,
+}
+
+/// Parses the optional parameters of a method, using their defaults when there are none.
+pub(super) fn parse_optional_params<T: for<'de> Deserialize<'de> + Default>(
+    method: &str,
+    params: Option<serde_json::Value>,
+) -> Result<T, BrpError> {
+    let Some(value) = params else {
+        return Ok(T::default());
+    };
+
+    serde_json::from_value::<T>(value).map_err(|_| BrpError {
+        code: PARSE_ERROR,
+        data: None,
+        message: format!("{method}: Unable to parse request"),
+    })
+}
+
+/// Runs `f` on the world of the requested session, or on the main world when there is none.
+pub(super) fn in_session<T>(
+    world: &mut World,
+    method: &str,
+    session: Option<&str>,
+    f: impl FnOnce(&mut World) -> Result<T, BrpError>,
+) -> Result<T, BrpError> {
+    let Some(id) = session else {
+        return f(world);
+    };
+    let Some(mut sessions) = world.get_non_send_resource_mut::<SimulationSessions>() else {
+        return Err(sessions_not_found(method));
+    };
+    let Some(session_world) = sessions.world_mut(id) else {
+        return Err(unknown_session(method, id));
+    };
+
+    f(session_world)
+}
+
+fn sessions_not_found(method: &str) -> BrpError {
+    BrpError {
+        code: INTERNAL_ERROR,
+        data: None,
+        message: format!("{method}: SimulationSessions resource not found"),
+    }
+}
+
+fn unknown_session(method: &str, id: &str) -> BrpError {
+    BrpError {
+        code: SESSION_NOT_FOUND,
+        data: Some(serde_json::json!({ "session": id })),
+        message: format!("{method}: Unknown session {id}"),
+    }
+}
+
+/// Starts a session from the current state of the main simulation, or from a named scenario,
+/// unless the most sessions allowed are already running.
+pub fn simulation_create_session(
+    In(params): In<Option<serde_json::Value>>,
+    world: &mut World,
+) -> BrpResult {
+    const METHOD: &str = "simulation.create_session";
+
+    let request = parse_optional_params::<CreateSessionRequest>(METHOD, params)?;
+    let mut snapshot = SimulationSnapshot::capture(world);
+
+    if let Some(name) = &request.scenario {
+        snapshot.config = Scenario::load_named(name)
+            .map_err(|error| scenario_error(METHOD, name, error))?
+            .config;
+    }
+
+    let Some(max_sessions) = world
+        .get_non_send_resource::<SimulationSessions>()
+        .map(SimulationSessions::max_sessions)
+    else {
+        return Err(sessions_not_found(METHOD));
+    };
+    // Sessions keep a history and time series with the settings of the main simulation, the
+    // history budget being shared by all of them.
+    let history = world
+        .get_resource::<SimulationHistory>()
+        .map(|history| history.empty_share(max_sessions));
+    let series = world
+        .get_resource::<FieldSeries>()
+        .map(FieldSeries::empty_copy);
+    let Some(mut sessions) = world.get_non_send_resource_mut::<SimulationSessions>() else {
+        return Err(sessions_not_found(METHOD));
+    };
+    let Some(session) = sessions.create(&snapshot) else {
+        return Err(BrpError {
+            code: TOO_MANY_SESSIONS,
+            data: Some(serde_json::json!({ "max_sessions": max_sessions })),
+            message: format!("{METHOD}: The limit of {max_sessions} sessions is reached"),
+        });
+    };
+
+    if let Some(session_world) = sessions.world_mut(&session) {
+        if let Some(history) = history {
+            session_world.insert_resource(history);
+        }
+        if let Some(series) = series {
+            session_world.insert_resource(series);
+        }
+    }
+
+    serde_json::to_value(SessionResponse { session }).map_err(BrpError::internal)
+}
+
+pub fn simulation_list_sessions(
+    In(_): In<Option<serde_json::Value>>,
+    world: &mut World,
+) -> BrpResult {
+    let Some(sessions) = world.get_non_send_resource::<SimulationSessions>() else {
+        return Err(sessions_not_found("simulation.list_sessions"));
+    };
+
+    serde_json::to_value(sessions.list()).map_err(BrpError::internal)
+}
+
+pub fn simulation_destroy_session(
+    In(params): In<Option<serde_json::Value>>,
+    world: &mut World,
+) -> BrpResult {
+    const METHOD: &str = "simulation.destroy_session";
+
+    let Some(value) = params else {
+        return Err(BrpError {
+            code: INVALID_REQUEST,
+            data: None,
+            message: format!("{METHOD}: Request was empty"),
+        });
+    };
+    let Ok(request) = serde_json::from_value::<DestroySessionRequest>(value) else {
+        return Err(BrpError {
+            code: PARSE_ERROR,
+            data: None,
+            message: format!("{METHOD}: Unable to parse request"),
+        });
+    };
+    let Some(mut sessions) = world.get_non_send_resource_mut::<SimulationSessions>() else {
+        return Err(sessions_not_found(METHOD));
+    };
+
+    if !sessions.remove(&request.session) {
+        return Err(unknown_session(METHOD, &request.session));
+    }
+
+    serde_json::to_value(SessionResponse {
+        session: request.session,
+    })
+    .map_err(BrpError::internal)
+}
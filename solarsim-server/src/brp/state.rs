@@ -0,0 +1,202 @@
+use std::collections::{BTreeMap, HashMap};
+
+use bevy::{
+    diagnostic::FrameCount,
+    ecs::{
+        system::{In, Local},
+        world::World,
+    },
+    remote::{BrpError, BrpResult, error_codes::INVALID_PARAMS},
+    time::{Real, Time},
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    in_session, parse_optional_params,
+    update_field::{config_not_found, field_error},
+};
+use crate::simulation::{FIELD_METADATA, FieldError, SimulationClock, SimulationConfig};
+
+#[derive(Deserialize, Default)]
+struct GetStateRequest {
+    /// Fields to stream, every field when empty
+    #[serde(default)]
+    pub fields: Option<Vec<String>>,
+    /// Smallest change of a field that is sent, any change is sent when zero
+    #[serde(default)]
+    pub deadband: f32,
+    /// Most updates sent per second, one after every frame with a change when absent
+    #[serde(default)]
+    pub rate: Option<f64>,
+    #[serde(default)]
+    pub session: Option<String>,
+    /// Id of the watching request, see [`STREAM_ID_PARAM`](super::STREAM_ID_PARAM)
+    #[serde(default)]
+    pub stream_id: Option<u64>,
+}
+
+/// State of the simulation, with the values of the requested fields.
+#[derive(Serialize, Clone)]
+struct StateUpdate {
+    pub step: u64,
+    /// Local date and time of the simulation
+    pub time: String,
+    pub fields: BTreeMap<String, f32>,
+}
+
+/// What was last sent to a `simulation.get_state+watch` request.
+#[derive(Default)]
+pub struct StateSubscription {
+    /// Values last sent, by field name
+    sent: BTreeMap<String, f32>,
+    /// Real time the last update was sent at, measured in seconds
+    sent_at: Option<f64>,
+    /// Frame the request was last watched in
+    frame: u32,
+}
+
+fn parse_request(
+    method: &str,
+    params: Option<serde_json::Value>,
+) -> Result<GetStateRequest, BrpError> {
+    let request = parse_optional_params::<GetStateRequest>(method, params)?;
+
+    if request.deadband.is_nan() || request.deadband < 0.0 {
+        return Err(BrpError {
+            code: INVALID_PARAMS,
+            data: None,
+            message: format!("{method}: deadband must be zero or positive"),
+        });
+    }
+
+    if request
+        .rate
+        .is_some_and(|rate| rate.is_nan() || rate <= 0.0)
+    {
+        return Err(BrpError {
+            code: INVALID_PARAMS,
+            data: None,
+            message: format!("{method}: rate must be positive"),
+        });
+    }
+
+    Ok(request)
+}
+
+/// Current state of the simulation, with the values of `fields` or of every field.
+fn current_state(
+    world: &World,
+    method: &str,
+    fields: Option<&[String]>,
+) -> Result<StateUpdate, BrpError> {
+    let Some(cfg) = world.get_resource::<SimulationConfig>() else {
+        return Err(config_not_found(method));
+    };
+    let names = match fields {
+        Some(fields) => fields.iter().map(String::as_str).collect::<Vec<_>>(),
+        None => FIELD_METADATA
+            .iter()
+            .map(|metadata| metadata.name)
+            .collect(),
+    };
+    let fields = names
+        .into_iter()
+        .map(|name| match cfg.field(name) {
+            Some(field) => Ok((name.to_string(), **field)),
+            None => Err(field_error(method, name, FieldError::UnknownField)),
+        })
+        .collect::<Result<_, _>>()?;
+    let (step, time) = world
+        .get_resource::<SimulationClock>()
+        .map_or((0, String::new()), |clock| {
+            (clock.steps, clock.now().to_string())
+        });
+
+    Ok(StateUpdate { step, time, fields })
+}
+
+/// Returns the current state of the simulation, to start from before watching it.
+pub fn simulation_get_state(
+    In(params): In<Option<serde_json::Value>>,
+    world: &mut World,
+) -> BrpResult {
+    const METHOD: &str = "simulation.get_state";
+
+    let request = parse_request(METHOD, params)?;
+
+    in_session(world, METHOD, request.session.as_deref(), |world| {
+        let state = current_state(world, METHOD, request.fields.as_deref())?;
+
+        serde_json::to_value(state).map_err(BrpError::internal)
+    })
+}
+
+/// Streams the state of the simulation, sending the fields that changed by more than the
+/// deadband, at most once per frame or at the requested rate.
+///
+/// The first update holds every requested field.
+pub fn simulation_get_state_watch(
+    In(params): In<Option<serde_json::Value>>,
+    world: &mut World,
+    mut subscriptions: Local<HashMap<String, StateSubscription>>,
+) -> BrpResult<Option<serde_json::Value>> {
+    const METHOD: &str = "simulation.get_state+watch";
+
+    let request = parse_request(METHOD, params.clone())?;
+    // The HTTP server gives every watching request its own id. Without one, identical requests
+    // can't be told apart and would share the updates of a single subscription.
+    let key = match request.stream_id {
+        Some(stream_id) => stream_id.to_string(),
+        None => params.map_or_else(String::new, |params| params.to_string()),
+    };
+    let frame = world
+        .get_resource::<FrameCount>()
+        .map_or(0, |frame| frame.0);
+    let now = world
+        .get_resource::<Time<Real>>()
+        .map_or(0.0, Time::elapsed_secs_f64);
+
+    // Subscriptions not watched in the previous frame belong to closed requests.
+    subscriptions.retain(|_, subscription| frame.wrapping_sub(subscription.frame) <= 1);
+
+    let subscription = subscriptions.entry(key).or_default();
+
+    subscription.frame = frame;
+
+    let state = in_session(world, METHOD, request.session.as_deref(), |world| {
+        current_state(world, METHOD, request.fields.as_deref())
+    })?;
+    let min_interval = request.rate.map_or(0.0, |rate| 1.0 / rate);
+
+    if subscription
+        .sent_at
+        .is_some_and(|sent_at| now - sent_at < min_interval)
+    {
+        return Ok(None);
+    }
+
+    let changed = state
+        .fields
+        .into_iter()
+        .filter(|(name, value)| {
+            subscription
+                .sent
+                .get(name)
+                .is_none_or(|sent| (value - sent).abs() > request.deadband)
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    if changed.is_empty() {
+        return Ok(None);
+    }
+
+    subscription.sent.extend(changed.clone());
+    subscription.sent_at = Some(now);
+
+    serde_json::to_value(StateUpdate {
+        fields: changed,
+        ..state
+    })
+    .map(Some)
+    .map_err(BrpError::internal)
+}
@@ -1,59 +1,210 @@
+use std::collections::BTreeMap;
+
 use bevy::{
     ecs::{system::In, world::World},
-    reflect::PartialReflect,
     remote::{
         BrpError, BrpResult,
-        error_codes::{INTERNAL_ERROR, INVALID_REQUEST, PARSE_ERROR},
+        error_codes::{INTERNAL_ERROR, INVALID_PARAMS, INVALID_REQUEST, PARSE_ERROR},
     },
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::simulation::{SimulationConfig, SimulationField};
+use super::{
+    error_codes::{OUT_OF_RANGE, READ_ONLY_FIELD, UNKNOWN_FIELD},
+    in_session,
+};
+use crate::simulation::{
+    ChangeSource, FieldChange, FieldError, FieldUpdateMode, InputRecorder, RecordedChange,
+    SimulationClock, SimulationConfig,
+};
 
 #[derive(Deserialize)]
 struct UpdateSimulationFieldValueRequest {
     pub field_name: String,
     pub value: f32,
+    #[serde(flatten)]
+    pub mode: FieldUpdateMode,
+    #[serde(default)]
+    pub session: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct UpdateSimulationFieldsRequest {
+    /// New value of every field to update, by field name
+    pub fields: BTreeMap<String, f32>,
+    #[serde(flatten)]
+    pub mode: FieldUpdateMode,
+    #[serde(default)]
+    pub session: Option<String>,
+}
+
+/// Result of a field update, returned on success.
+#[derive(Serialize)]
+struct FieldUpdateResponse {
+    pub field: String,
+    #[serde(flatten)]
+    pub change: FieldChange,
+}
+
+/// Details of a refused field update, returned as error data.
+#[derive(Serialize)]
+struct FieldErrorData {
+    pub code: i16,
+    pub field: String,
+    #[serde(flatten)]
+    pub error: FieldError,
+}
+
+impl FieldErrorData {
+    fn new(field: String, error: FieldError) -> Self {
+        let code = match error {
+            FieldError::UnknownField => UNKNOWN_FIELD,
+            FieldError::ReadOnly => READ_ONLY_FIELD,
+            FieldError::OutOfRange { .. } => OUT_OF_RANGE,
+        };
+
+        Self { code, field, error }
+    }
+}
+
+/// Error of a refused update or read of `field`, with its details as data.
+pub(super) fn field_error(method: &str, field: &str, error: FieldError) -> BrpError {
+    let data = FieldErrorData::new(field.to_string(), error);
+
+    BrpError {
+        code: data.code,
+        message: format!("{method}: {field}: {error}"),
+        data: serde_json::to_value(data).ok(),
+    }
 }
 
+pub(super) fn config_not_found(method: &str) -> BrpError {
+    BrpError {
+        code: INTERNAL_ERROR,
+        data: None,
+        message: format!("{method}: SimulationConfig resource not found"),
+    }
+}
+
+/// Sets a field, refusing read-only fields and out-of-range values unless the request's
+/// `override_read_only` or `clamp` flag is set. Only admins may set `override_read_only`.
 pub fn simulation_update_field(
     In(params): In<Option<serde_json::Value>>,
     world: &mut World,
 ) -> BrpResult {
+    const METHOD: &str = "simulation.update_field";
+
     let Some(value) = params else {
         return Err(BrpError {
             code: INVALID_REQUEST,
             data: None,
-            message: "simulation.update_field: Request was empty".to_string(),
+            message: format!("{METHOD}: Request was empty"),
         });
     };
-
     let Ok(request) = serde_json::from_value::<UpdateSimulationFieldValueRequest>(value) else {
         return Err(BrpError {
             code: PARSE_ERROR,
             data: None,
-            message: "simulation.update_field: Unable to parse request".to_string(),
+            message: format!("{METHOD}: Unable to parse request"),
         });
     };
 
-    if let Some(mut cfg) = world.get_resource_mut::<SimulationConfig>()
-        && let Ok(reflect_struct) = cfg.reflect_mut().as_struct()
-        && let Some(field) = reflect_struct.field_mut(&request.field_name)
-        && let Some(value) = field.try_downcast_mut::<SimulationField>()
-    {
-        let old_value = **value;
+    in_session(world, METHOD, request.session.as_deref(), |world| {
+        let step = world
+            .get_resource::<SimulationClock>()
+            .map_or(0, |clock| clock.steps);
+        let Some(mut cfg) = world.get_resource_mut::<SimulationConfig>() else {
+            return Err(config_not_found(METHOD));
+        };
+        let change = cfg
+            .update_field(&request.field_name, request.value, request.mode)
+            .map_err(|error| field_error(METHOD, &request.field_name, error))?;
 
-        **value = request.value;
+        // Only the main simulation records, sessions have no recorder.
+        if let Some(mut recorder) = world.get_resource_mut::<InputRecorder>() {
+            recorder.record(RecordedChange {
+                step,
+                source: ChangeSource::UpdateField,
+                field: request.field_name.clone(),
+                old_value: change.old_value,
+                new_value: change.new_value,
+            });
+        }
 
-        return Ok(serde_json::Value::String(format!(
-            "Updated SimulationConfig::{}: {} -> {}",
-            request.field_name, old_value, request.value
-        )));
-    }
+        serde_json::to_value(FieldUpdateResponse {
+            field: request.field_name,
+            change,
+        })
+        .map_err(BrpError::internal)
+    })
+}
 
-    return Err(BrpError {
-        code: INTERNAL_ERROR,
-        message: "simulation.update_field: Unknown field".to_string(),
-        data: None,
-    });
+/// Sets several fields in a single world access, all of them or none.
+///
+/// When a value is invalid no field is updated, and the `errors` of the error data hold the
+/// details of every invalid field.
+pub fn simulation_update_fields(
+    In(params): In<Option<serde_json::Value>>,
+    world: &mut World,
+) -> BrpResult {
+    const METHOD: &str = "simulation.update_fields";
+
+    let Some(value) = params else {
+        return Err(BrpError {
+            code: INVALID_REQUEST,
+            data: None,
+            message: format!("{METHOD}: Request was empty"),
+        });
+    };
+    let Ok(request) = serde_json::from_value::<UpdateSimulationFieldsRequest>(value) else {
+        return Err(BrpError {
+            code: PARSE_ERROR,
+            data: None,
+            message: format!("{METHOD}: Unable to parse request"),
+        });
+    };
+
+    in_session(world, METHOD, request.session.as_deref(), |world| {
+        let step = world
+            .get_resource::<SimulationClock>()
+            .map_or(0, |clock| clock.steps);
+        let Some(mut cfg) = world.get_resource_mut::<SimulationConfig>() else {
+            return Err(config_not_found(METHOD));
+        };
+        let changes = cfg
+            .update_fields(&request.fields, request.mode)
+            .map_err(|errors| BrpError {
+                code: INVALID_PARAMS,
+                message: format!(
+                    "{METHOD}: {} invalid fields, none was updated",
+                    errors.len()
+                ),
+                data: Some(serde_json::json!({
+                    "errors": errors
+                        .into_iter()
+                        .map(|(field, error)| FieldErrorData::new(field, error))
+                        .collect::<Vec<_>>(),
+                })),
+            })?;
+
+        if let Some(mut recorder) = world.get_resource_mut::<InputRecorder>() {
+            for (field, change) in &changes {
+                recorder.record(RecordedChange {
+                    step,
+                    source: ChangeSource::UpdateFields,
+                    field: field.clone(),
+                    old_value: change.old_value,
+                    new_value: change.new_value,
+                });
+            }
+        }
+
+        serde_json::to_value(
+            changes
+                .into_iter()
+                .map(|(field, change)| FieldUpdateResponse { field, change })
+                .collect::<Vec<_>>(),
+        )
+        .map_err(BrpError::internal)
+    })
 }
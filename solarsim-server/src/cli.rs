@@ -0,0 +1,841 @@
+use std::{
+    fs, io,
+    net::IpAddr,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    log::Level,
+    remote::http::{DEFAULT_ADDR, DEFAULT_PORT},
+};
+use clap::{Args, Parser, Subcommand, ValueEnum, builder::RangedU64ValueParser};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    FIXED_TIMESTEP_SECS,
+    cors::{CORS_HEADERS, CORS_METHODS, CORS_ORIGINS},
+    sessions::{MAX_SESSIONS, SESSION_TIMEOUT_SECS},
+    simulation::{
+        HISTORY_BUDGET_MIB, HISTORY_INTERVAL_STEPS, SERIES_INTERVAL_SECS, SERIES_RETENTION_SECS,
+    },
+};
+
+/// Solar thermal system simulation server, remotely controlled through the Bevy Remote Protocol.
+#[derive(Parser, Debug)]
+#[command(version)]
+pub struct Cli {
+    /// TOML file with server options, named like the long options below in snake_case.
+    /// Options given on the command line take precedence over the file.
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    #[command(flatten)]
+    pub options: ServerOptions,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Options of the server, set on the command line or in the `--config` file.
+#[derive(Args, Deserialize, Clone, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerOptions {
+    /// Address the BRP HTTP server binds to [default: 127.0.0.1]
+    #[arg(long)]
+    pub address: Option<IpAddr>,
+    /// Port the BRP HTTP server listens on [default: 15702]
+    #[arg(long)]
+    pub port: Option<u16>,
+    /// Size of a simulation step, in seconds or with a `s`, `m`, `h` or `d` suffix. In the config
+    /// file it's a number of seconds [default: 0.5]
+    #[arg(long, global = true, value_parser = parse_duration)]
+    pub timestep: Option<f64>,
+    /// Origin allowed to make cross-origin BRP requests, as `scheme://host[:port]`
+    /// [default: http://localhost:3000, http://127.0.0.1:3000]
+    #[arg(long = "cors-origin", value_name = "ORIGIN", value_parser = parse_origin)]
+    pub cors_origins: Option<Vec<String>>,
+    /// Allow cross-origin BRP requests from any origin, instead of the `--cors-origin` ones.
+    /// Only meant for development: any web page opened in a browser on the network can then
+    /// call the server.
+    #[arg(long)]
+    pub cors_any_origin: bool,
+    /// HTTP method allowed in cross-origin BRP requests [default: POST]
+    #[arg(long = "cors-method", value_name = "METHOD", value_parser = parse_http_token)]
+    pub cors_methods: Option<Vec<String>>,
+    /// Header allowed in cross-origin BRP requests [default: Content-Type, Authorization]
+    #[arg(long = "cors-header", value_name = "HEADER", value_parser = parse_http_token)]
+    pub cors_headers: Option<Vec<String>>,
+    /// Scenario file to start from instead of the default parameters.
+    #[arg(long, global = true, value_name = "PATH")]
+    pub scenario: Option<PathBuf>,
+    /// Most verbose log messages printed, `RUST_LOG` takes precedence [default: info]
+    #[arg(long, global = true, value_enum)]
+    pub log_level: Option<LogLevel>,
+    /// Run without a window, renderer or UI. The simulation is only reachable through BRP.
+    ///
+    /// Always enabled when the server is built without the `gui` feature.
+    #[arg(
+        long,
+        num_args = 0,
+        default_missing_value = "true",
+        overrides_with = "no_headless"
+    )]
+    pub headless: Option<bool>,
+    /// Open the window even when the `--config` file sets `headless = true`.
+    #[arg(long, overrides_with = "headless")]
+    #[serde(skip)]
+    pub no_headless: bool,
+    /// File every change made to the simulation fields through the UI or BRP is recorded to,
+    /// to reproduce the session with `batch --replay`.
+    #[arg(long, value_name = "PATH")]
+    pub record: Option<PathBuf>,
+    /// Width of the window in pixels [default: 1280]
+    #[arg(long)]
+    pub window_width: Option<u32>,
+    /// Height of the window in pixels [default: 720]
+    #[arg(long)]
+    pub window_height: Option<u32>,
+    /// Time after which BRP sessions that were not accessed are removed, same format as
+    /// `--timestep`. In the config file it's a number of seconds [default: 30m]
+    #[arg(long, value_parser = parse_duration)]
+    pub session_timeout: Option<f64>,
+    /// Most BRP sessions running at once, `0` disables them. They share the history budget
+    /// [default: 8]
+    #[arg(long, value_name = "COUNT")]
+    pub max_sessions: Option<usize>,
+    /// Steps between two entries of the simulation history [default: 1]
+    #[arg(long, value_name = "STEPS", value_parser = RangedU64ValueParser::<u64>::new().range(1..))]
+    pub history_interval: Option<u64>,
+    /// Memory the simulation history may use, in MiB, `0` disables it [default: 64]
+    #[arg(long, value_name = "MIB")]
+    pub history_budget: Option<u64>,
+    /// Simulated time between two samples of the field time series, same format as `--timestep`.
+    /// In the config file it's a number of seconds [default: 1m]
+    #[arg(long, value_parser = parse_duration)]
+    pub series_interval: Option<f64>,
+    /// Simulated time the samples of the field time series are kept for, same format as
+    /// `--timestep`. In the config file it's a number of seconds [default: 7d]
+    #[arg(long, value_parser = parse_duration)]
+    pub series_retention: Option<f64>,
+    /// TOML file with the access tokens of the BRP clients and their roles. Without it, every
+    /// client can call every method.
+    #[arg(long, value_name = "PATH")]
+    pub tokens: Option<PathBuf>,
+}
+
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io {
+        path: PathBuf,
+        error: io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        error: toml::de::Error,
+    },
+    Invalid(String),
+}
+
+impl core::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Io { path, error } => write!(f, "{}: {error}", path.display()),
+            Self::Parse { path, error } => write!(f, "{}: {error}", path.display()),
+            Self::Invalid(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl core::error::Error for ConfigError {}
+
+impl Cli {
+    /// Options from the command line, completed with those of the `--config` file.
+    pub fn resolve_options(&self) -> Result<ServerOptions, ConfigError> {
+        let Some(path) = &self.config else {
+            return Ok(self.options.clone());
+        };
+        let file_options = ServerOptions::load(path)?;
+
+        Ok(self.options.clone().or(file_options))
+    }
+}
+
+impl ServerOptions {
+    fn load(path: &Path) -> Result<Self, ConfigError> {
+        let toml = fs::read_to_string(path).map_err(|error| ConfigError::Io {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        let options = toml::from_str::<Self>(&toml).map_err(|error| ConfigError::Parse {
+            path: path.to_path_buf(),
+            error,
+        })?;
+
+        for (name, duration) in [
+            ("timestep", options.timestep),
+            ("session_timeout", options.session_timeout),
+            ("series_interval", options.series_interval),
+            ("series_retention", options.series_retention),
+        ] {
+            if let Some(duration) = duration
+                && (!duration.is_finite() || duration <= 0.0)
+            {
+                return Err(ConfigError::Invalid(format!(
+                    "{}: {name} {duration} must be positive",
+                    path.display()
+                )));
+            }
+        }
+
+        let origins = options
+            .cors_origins
+            .iter()
+            .flatten()
+            .map(|origin| parse_origin(origin));
+        let tokens = (options.cors_methods.iter().flatten())
+            .chain(options.cors_headers.iter().flatten())
+            .map(|token| parse_http_token(token));
+
+        for result in origins.chain(tokens) {
+            if let Err(error) = result {
+                return Err(ConfigError::Invalid(format!("{}: {error}", path.display())));
+            }
+        }
+
+        if options.history_interval == Some(0) {
+            return Err(ConfigError::Invalid(format!(
+                "{}: history_interval must be at least 1",
+                path.display()
+            )));
+        }
+
+        Ok(options)
+    }
+
+    /// Fills the options not set in `self` with those of `other`.
+    fn or(self, other: Self) -> Self {
+        Self {
+            address: self.address.or(other.address),
+            port: self.port.or(other.port),
+            timestep: self.timestep.or(other.timestep),
+            cors_origins: self.cors_origins.or(other.cors_origins),
+            cors_any_origin: self.cors_any_origin || other.cors_any_origin,
+            cors_methods: self.cors_methods.or(other.cors_methods),
+            cors_headers: self.cors_headers.or(other.cors_headers),
+            scenario: self.scenario.or(other.scenario),
+            log_level: self.log_level.or(other.log_level),
+            headless: match self.no_headless {
+                true => Some(false),
+                false => self.headless,
+            }
+            .or(other.headless),
+            no_headless: false,
+            record: self.record.or(other.record),
+            window_width: self.window_width.or(other.window_width),
+            window_height: self.window_height.or(other.window_height),
+            session_timeout: self.session_timeout.or(other.session_timeout),
+            max_sessions: self.max_sessions.or(other.max_sessions),
+            history_interval: self.history_interval.or(other.history_interval),
+            history_budget: self.history_budget.or(other.history_budget),
+            series_interval: self.series_interval.or(other.series_interval),
+            series_retention: self.series_retention.or(other.series_retention),
+            tokens: self.tokens.or(other.tokens),
+        }
+    }
+
+    pub fn address(&self) -> IpAddr {
+        self.address.unwrap_or(DEFAULT_ADDR)
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port.unwrap_or(DEFAULT_PORT)
+    }
+
+    pub fn timestep_secs(&self) -> f64 {
+        self.timestep.unwrap_or(FIXED_TIMESTEP_SECS)
+    }
+
+    pub fn session_timeout_secs(&self) -> f64 {
+        self.session_timeout.unwrap_or(SESSION_TIMEOUT_SECS)
+    }
+
+    pub fn max_sessions(&self) -> usize {
+        self.max_sessions.unwrap_or(MAX_SESSIONS)
+    }
+
+    pub fn history_interval(&self) -> u64 {
+        self.history_interval.unwrap_or(HISTORY_INTERVAL_STEPS)
+    }
+
+    pub fn history_budget_bytes(&self) -> usize {
+        let mib = self.history_budget.unwrap_or(HISTORY_BUDGET_MIB);
+
+        usize::try_from(mib.saturating_mul(1024 * 1024)).unwrap_or(usize::MAX)
+    }
+
+    pub fn series_interval_secs(&self) -> f64 {
+        self.series_interval.unwrap_or(SERIES_INTERVAL_SECS)
+    }
+
+    pub fn series_retention_secs(&self) -> f64 {
+        self.series_retention.unwrap_or(SERIES_RETENTION_SECS)
+    }
+
+    /// Origins allowed to make cross-origin requests, any origin when `None`.
+    pub fn cors_origins(&self) -> Option<Vec<String>> {
+        if self.cors_any_origin {
+            return None;
+        }
+
+        Some(
+            self.cors_origins
+                .clone()
+                .unwrap_or_else(|| to_strings(CORS_ORIGINS)),
+        )
+    }
+
+    pub fn cors_methods(&self) -> Vec<String> {
+        self.cors_methods
+            .clone()
+            .unwrap_or_else(|| to_strings(CORS_METHODS))
+    }
+
+    pub fn cors_headers(&self) -> Vec<String> {
+        self.cors_headers
+            .clone()
+            .unwrap_or_else(|| to_strings(CORS_HEADERS))
+    }
+
+    pub fn log_level(&self) -> Level {
+        match self.log_level.unwrap_or(LogLevel::Info) {
+            LogLevel::Error => Level::ERROR,
+            LogLevel::Warn => Level::WARN,
+            LogLevel::Info => Level::INFO,
+            LogLevel::Debug => Level::DEBUG,
+            LogLevel::Trace => Level::TRACE,
+        }
+    }
+
+    pub fn headless(&self) -> bool {
+        self.headless.unwrap_or(false) || !cfg!(feature = "gui")
+    }
+
+    #[cfg(feature = "gui")]
+    pub fn window_size(&self) -> (u32, u32) {
+        (
+            self.window_width.unwrap_or(1280),
+            self.window_height.unwrap_or(720),
+        )
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Simulate a period as fast as possible and write the results to a file.
+    Batch(BatchArgs),
+    /// Simulate a period for every combination of field values and write a table of results.
+    Sweep(SweepArgs),
+    /// Search for the field values that minimize an objective while meeting constraints.
+    Optimize(OptimizeArgs),
+    /// Simulate a period many times with uncertain field values drawn at random, and write the
+    /// spread of the results.
+    MonteCarlo(MonteCarloArgs),
+    /// Upgrade scenario files saved by older versions to the current format, in place.
+    UpgradeScenarios {
+        /// Scenario files to upgrade.
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+    },
+}
+
+#[derive(Args, Debug)]
+pub struct BatchArgs {
+    /// Simulated period to run, in seconds or with a `s`, `m`, `h` or `d` suffix (e.g. `365d`).
+    ///
+    /// When replaying, defaults to one step past the last recorded change.
+    #[arg(long, value_parser = parse_duration, required_unless_present = "replay")]
+    pub duration: Option<f64>,
+    /// Recording (see `--record`) to replay. The run starts from the recorded state and
+    /// re-applies the recorded changes, `--scenario` and `--timestep` are ignored.
+    #[arg(long, value_name = "PATH")]
+    pub replay: Option<PathBuf>,
+    /// File the results are written to. Results are written to stdout when omitted.
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+    /// Format of the results.
+    #[arg(long, value_enum, default_value_t = BatchFormat::Csv)]
+    pub format: BatchFormat,
+    /// Simulated time between two result samples, same format as `--duration`.
+    #[arg(long, value_parser = parse_duration, default_value = "1h")]
+    pub interval: f64,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum BatchFormat {
+    /// One row per sample, with a header row.
+    Csv,
+    /// The samples and the final energy totals.
+    Json,
+}
+
+#[derive(Args, Debug)]
+pub struct SweepArgs {
+    /// Simulated period of every run, same format as `batch --duration`.
+    #[arg(long, value_parser = parse_duration)]
+    pub duration: f64,
+    /// Values of a `SimulationField`, as `name=start:end:count` for evenly spaced values or
+    /// `name=v1,v2,...` for a list. Repeat to sweep several fields.
+    #[arg(long = "param", value_name = "FIELD=VALUES", required = true, value_parser = parse_sweep_param)]
+    pub params: Vec<SweepParam>,
+    /// Vary one field at a time from the `--scenario` values to its lowest and highest value,
+    /// and rank the fields by their effect on `--metric`, instead of running every combination.
+    #[arg(long)]
+    pub sensitivity: bool,
+    /// Result the sensitivity of the fields is measured on, one of the batch energy totals.
+    #[arg(long, default_value = "solar_fraction")]
+    pub metric: String,
+    /// File the results are written to. Results are written to stdout when omitted.
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+    /// Format of the results.
+    #[arg(long, value_enum, default_value_t = BatchFormat::Csv)]
+    pub format: BatchFormat,
+    /// Number of runs simulated in parallel. Defaults to the number of available cores.
+    #[arg(long, short)]
+    pub jobs: Option<usize>,
+}
+
+#[derive(Args, Debug)]
+pub struct OptimizeArgs {
+    /// Simulated period of every run, same format as `batch --duration`.
+    #[arg(long, value_parser = parse_duration)]
+    pub duration: f64,
+    /// `SimulationField` to optimize, as `name` to search its whole range or `name=min:max`.
+    /// Repeat to optimize several fields.
+    #[arg(long = "field", value_name = "FIELD[=MIN:MAX]", required = true, value_parser = parse_design_field)]
+    pub fields: Vec<DesignField>,
+    /// Quantity to minimize.
+    #[arg(long, value_enum, default_value_t = Objective::LifeCycleCost)]
+    pub objective: Objective,
+    /// Lowest acceptable solar fraction (0.0-1.0).
+    #[arg(long)]
+    pub min_solar_fraction: Option<f64>,
+    /// Highest acceptable time with the tank above 80 °C over the simulated period, in hours.
+    #[arg(long)]
+    pub max_overheating_hours: Option<f64>,
+    #[command(flatten)]
+    pub costs: CostModel,
+    /// Number of simulation runs after which the search stops.
+    #[arg(long, default_value_t = 200)]
+    pub max_evaluations: usize,
+    /// File the results are written to. Results are written to stdout when omitted.
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+    /// Format of the results: the convergence history as CSV, or the optimum and its history
+    /// as JSON.
+    #[arg(long, value_enum, default_value_t = BatchFormat::Csv)]
+    pub format: BatchFormat,
+    /// Number of runs simulated in parallel. Defaults to the number of available cores.
+    #[arg(long, short)]
+    pub jobs: Option<usize>,
+}
+
+#[derive(ValueEnum, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Objective {
+    /// Cost of the collector and tank plus the discounted cost of the auxiliary energy over
+    /// the lifetime of the system.
+    LifeCycleCost,
+    /// Energy added by the auxiliary heater over the simulated period.
+    AuxEnergy,
+}
+
+/// Prices used by the life-cycle cost objective, in any currency.
+#[derive(Args, Clone, Debug)]
+pub struct CostModel {
+    /// Installed cost of the collector per m² of `panel_area`.
+    #[arg(long, default_value_t = 400.0)]
+    pub panel_cost: f64,
+    /// Installed cost of the tank per kg of `tank_water_mass`.
+    #[arg(long, default_value_t = 3.0)]
+    pub tank_cost: f64,
+    /// Price of the auxiliary energy per kWh.
+    #[arg(long, default_value_t = 0.25)]
+    pub energy_price: f64,
+    /// Years the system is in service.
+    #[arg(long, default_value_t = 20.0)]
+    pub lifetime: f64,
+    /// Yearly rate future energy costs are discounted at.
+    #[arg(long, default_value_t = 0.05)]
+    pub discount_rate: f64,
+}
+
+/// A field searched by the optimizer, with the range to search when not its whole range.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DesignField {
+    pub field: String,
+    pub range: Option<(f32, f32)>,
+}
+
+#[derive(Args, Debug)]
+pub struct MonteCarloArgs {
+    /// Simulated period of every run, same format as `batch --duration`.
+    #[arg(long, value_parser = parse_duration)]
+    pub duration: f64,
+    /// Distribution of a `SimulationField`, as `name=normal:mean:std_dev`,
+    /// `name=uniform:min:max` or `name=triangular:min:mode:max`. Repeat for several fields.
+    #[arg(long = "vary", value_name = "FIELD=DISTRIBUTION", required = true, value_parser = parse_uncertainty)]
+    pub uncertainties: Vec<Uncertainty>,
+    /// Number of simulation runs.
+    #[arg(long, default_value_t = 100, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    pub runs: usize,
+    /// Seed of the random field values, the same seed always draws the same values.
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+    /// File the results are written to. Results are written to stdout when omitted.
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+    /// Format of the results: the statistics of every energy total as CSV, or the statistics
+    /// and every run as JSON.
+    #[arg(long, value_enum, default_value_t = BatchFormat::Csv)]
+    pub format: BatchFormat,
+    /// Number of runs simulated in parallel. Defaults to the number of available cores.
+    #[arg(long, short)]
+    pub jobs: Option<usize>,
+}
+
+/// An uncertain field and the distribution its values are drawn from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Uncertainty {
+    pub field: String,
+    pub distribution: Distribution,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Distribution {
+    Normal { mean: f64, std_dev: f64 },
+    Uniform { min: f64, max: f64 },
+    Triangular { min: f64, mode: f64, max: f64 },
+}
+
+/// Values a field takes in a sweep.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SweepParam {
+    pub field: String,
+    pub values: Vec<f32>,
+}
+
+fn to_strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(ToString::to_string).collect()
+}
+
+/// Parses an origin, `scheme://host[:port]` without path, as sent by browsers.
+fn parse_origin(value: &str) -> Result<String, String> {
+    if value == "*" {
+        return Err(
+            "the `*` origin is not supported, use --cors-any-origin to allow any origin \
+            during development"
+                .to_string(),
+        );
+    }
+
+    let host = value
+        .strip_prefix("http://")
+        .or_else(|| value.strip_prefix("https://"))
+        .ok_or_else(|| format!("origin `{value}` must start with http:// or https://"))?;
+
+    if host.is_empty() || host.contains('/') || !host.chars().all(|c| c.is_ascii_graphic()) {
+        return Err(format!(
+            "origin `{value}` must be `scheme://host[:port]`, without path"
+        ));
+    }
+
+    Ok(value.to_string())
+}
+
+/// Parses an HTTP method or header name.
+fn parse_http_token(value: &str) -> Result<String, String> {
+    if value.is_empty()
+        || !value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c))
+    {
+        return Err(format!(
+            "`{value}` is not a valid HTTP method or header name"
+        ));
+    }
+
+    Ok(value.to_string())
+}
+
+/// Parses a number of seconds, optionally followed by a `s`, `m`, `h` or `d` unit.
+fn parse_duration(value: &str) -> Result<f64, String> {
+    let value = value.trim();
+    let (number, unit_secs) = match value.char_indices().last() {
+        Some((index, 's')) => (&value[..index], 1.0),
+        Some((index, 'm')) => (&value[..index], 60.0),
+        Some((index, 'h')) => (&value[..index], 3600.0),
+        Some((index, 'd')) => (&value[..index], 86400.0),
+        _ => (value, 1.0),
+    };
+    let number = number
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| format!("invalid duration `{value}`"))?;
+
+    if !number.is_finite() || number <= 0.0 {
+        return Err(format!("duration `{value}` must be positive"));
+    }
+
+    Ok(number * unit_secs)
+}
+
+/// Parses `name=start:end:count` or `name=v1,v2,...` into the values of a sweep parameter.
+fn parse_sweep_param(value: &str) -> Result<SweepParam, String> {
+    let Some((field, values)) = value.split_once('=') else {
+        return Err(format!("expected `name=values`, got `{value}`"));
+    };
+    let number = |number: &str| {
+        number
+            .trim()
+            .parse::<f32>()
+            .ok()
+            .filter(|number| number.is_finite())
+            .ok_or_else(|| format!("invalid value `{number}` for {field}"))
+    };
+    let values = match values.split(':').collect::<Vec<_>>()[..] {
+        [start, end, count] => {
+            let (start, end) = (number(start)?, number(end)?);
+            let count = count
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|count| *count >= 2)
+                .ok_or_else(|| format!("the count of {field} values must be at least 2"))?;
+
+            (0..count)
+                .map(|index| start + (end - start) * index as f32 / (count - 1) as f32)
+                .collect()
+        }
+        [values] => values.split(',').map(number).collect::<Result<_, _>>()?,
+        _ => return Err(format!("expected `start:end:count` or a list for {field}")),
+    };
+
+    Ok(SweepParam {
+        field: field.trim().to_string(),
+        values,
+    })
+}
+
+/// Parses `name` or `name=min:max` into a field searched by the optimizer.
+fn parse_design_field(value: &str) -> Result<DesignField, String> {
+    let Some((field, range)) = value.split_once('=') else {
+        return Ok(DesignField {
+            field: value.trim().to_string(),
+            range: None,
+        });
+    };
+    let invalid = || format!("expected `{field}=min:max` with min below max, got `{value}`");
+    let (min, max) = range.split_once(':').ok_or_else(invalid)?;
+    let (min, max) = (
+        min.trim().parse::<f32>().map_err(|_| invalid())?,
+        max.trim().parse::<f32>().map_err(|_| invalid())?,
+    );
+
+    if !(min.is_finite() && max.is_finite() && min < max) {
+        return Err(invalid());
+    }
+
+    Ok(DesignField {
+        field: field.trim().to_string(),
+        range: Some((min, max)),
+    })
+}
+
+/// Parses `name=normal:mean:std_dev`, `name=uniform:min:max` or `name=triangular:min:mode:max`.
+fn parse_uncertainty(value: &str) -> Result<Uncertainty, String> {
+    let Some((field, distribution)) = value.split_once('=') else {
+        return Err(format!("expected `name=distribution`, got `{value}`"));
+    };
+    let mut parts = distribution.split(':');
+    let kind = parts.next().unwrap_or_default().trim();
+    let numbers = parts
+        .map(|number| number.trim().parse::<f64>().ok().filter(|n| n.is_finite()))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| format!("invalid number in `{distribution}`"))?;
+    let distribution = match (kind, &numbers[..]) {
+        ("normal", &[mean, std_dev]) if std_dev >= 0.0 => Distribution::Normal { mean, std_dev },
+        ("uniform", &[min, max]) if min < max => Distribution::Uniform { min, max },
+        ("triangular", &[min, mode, max]) if min <= mode && mode <= max && min < max => {
+            Distribution::Triangular { min, mode, max }
+        }
+        _ => {
+            return Err(format!(
+                "expected `normal:mean:std_dev`, `uniform:min:max` or \
+                 `triangular:min:mode:max` in order for {field}, got `{distribution}`"
+            ));
+        }
+    };
+
+    Ok(Uncertainty {
+        field: field.trim().to_string(),
+        distribution,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::default;
+
+    use super::*;
+
+    #[test]
+    fn durations_accept_units() {
+        assert_eq!(parse_duration("90"), Ok(90.0));
+        assert_eq!(parse_duration("1.5m"), Ok(90.0));
+        assert_eq!(parse_duration("2h"), Ok(7200.0));
+        assert_eq!(parse_duration("365d"), Ok(365.0 * 86400.0));
+    }
+
+    #[test]
+    fn command_line_options_take_precedence_over_the_config_file() {
+        let cli = ServerOptions {
+            port: Some(8080),
+            ..default()
+        };
+        let file = toml::from_str::<ServerOptions>(
+            "port = 9000\ntimestep = 60.0\ncors_origins = [\"http://localhost:3000\"]\nlog_level = \"debug\"",
+        )
+        .unwrap();
+        let options = cli.or(file);
+
+        assert_eq!(options.port(), 8080);
+        assert_eq!(options.timestep_secs(), 60.0);
+        assert_eq!(
+            options.cors_origins(),
+            Some(vec!["http://localhost:3000".to_string()])
+        );
+        assert_eq!(options.log_level(), Level::DEBUG);
+        assert_eq!(options.address(), DEFAULT_ADDR);
+    }
+
+    #[test]
+    fn the_window_can_be_turned_back_on() {
+        let file = toml::from_str::<ServerOptions>("headless = true").unwrap();
+        let parse = |args: &[&str]| {
+            Cli::try_parse_from([&["solarsim-server"], args].concat())
+                .unwrap()
+                .options
+                .or(file.clone())
+                .headless
+        };
+
+        assert_eq!(parse(&[]), Some(true));
+        assert_eq!(parse(&["--no-headless"]), Some(false));
+        assert_eq!(parse(&["--no-headless", "--headless"]), Some(true));
+        assert_eq!(parse(&["--headless", "--no-headless"]), Some(false));
+    }
+
+    #[test]
+    fn unknown_config_options_are_rejected() {
+        assert!(toml::from_str::<ServerOptions>("prot = 9000").is_err());
+    }
+
+    #[test]
+    fn cors_origins_must_be_explicit() {
+        assert!(parse_origin("https://dashboard.intranet:8443").is_ok());
+        assert!(parse_origin("*").is_err());
+        assert!(parse_origin("dashboard.intranet").is_err());
+        assert!(parse_origin("https://dashboard.intranet/").is_err());
+
+        let options = ServerOptions {
+            cors_any_origin: true,
+            ..default()
+        };
+
+        assert_eq!(options.cors_origins(), None);
+        assert_eq!(
+            ServerOptions::default().cors_origins(),
+            Some(to_strings(CORS_ORIGINS))
+        );
+    }
+
+    #[test]
+    fn invalid_durations_are_rejected() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("-1h").is_err());
+        assert!(parse_duration("1w").is_err());
+    }
+
+    #[test]
+    fn sweep_params_accept_ranges_and_lists() {
+        assert_eq!(
+            parse_sweep_param("panel_area=2:4:3"),
+            Ok(SweepParam {
+                field: "panel_area".to_string(),
+                values: vec![2.0, 3.0, 4.0],
+            })
+        );
+        assert_eq!(
+            parse_sweep_param("panel_efficiency=0.6,0.75").map(|param| param.values),
+            Ok(vec![0.6, 0.75])
+        );
+        assert!(parse_sweep_param("panel_area").is_err());
+        assert!(parse_sweep_param("panel_area=2:4:1").is_err());
+        assert!(parse_sweep_param("panel_area=2:4").is_err());
+        assert!(parse_sweep_param("panel_area=a,b").is_err());
+    }
+
+    #[test]
+    fn design_fields_take_an_optional_range() {
+        assert_eq!(
+            parse_design_field("panel_area"),
+            Ok(DesignField {
+                field: "panel_area".to_string(),
+                range: None,
+            })
+        );
+        assert_eq!(
+            parse_design_field("tank_water_mass=50:300").map(|field| field.range),
+            Ok(Some((50.0, 300.0)))
+        );
+        assert!(parse_design_field("panel_area=3:1").is_err());
+        assert!(parse_design_field("panel_area=1").is_err());
+    }
+
+    #[test]
+    fn uncertainties_accept_every_distribution() {
+        assert_eq!(
+            parse_uncertainty("panel_efficiency=normal:0.7:0.05").map(|u| u.distribution),
+            Ok(Distribution::Normal {
+                mean: 0.7,
+                std_dev: 0.05,
+            })
+        );
+        assert_eq!(
+            parse_uncertainty("load_temp=uniform:8:14").map(|u| u.distribution),
+            Ok(Distribution::Uniform {
+                min: 8.0,
+                max: 14.0,
+            })
+        );
+        assert_eq!(
+            parse_uncertainty("panel_heat_loss_coefficient=triangular:3:4:6").map(|u| u.field),
+            Ok("panel_heat_loss_coefficient".to_string())
+        );
+        assert!(parse_uncertainty("panel_area=normal:2").is_err());
+        assert!(parse_uncertainty("panel_area=normal:2:-1").is_err());
+        assert!(parse_uncertainty("panel_area=uniform:3:1").is_err());
+        assert!(parse_uncertainty("panel_area=triangular:1:5:3").is_err());
+        assert!(parse_uncertainty("panel_area=lognormal:1:2").is_err());
+    }
+}
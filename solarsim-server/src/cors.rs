@@ -0,0 +1,216 @@
+use hyper::{
+    HeaderMap,
+    header::{
+        ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN,
+        ACCESS_CONTROL_MAX_AGE, ACCESS_CONTROL_REQUEST_HEADERS, ACCESS_CONTROL_REQUEST_METHOD,
+        HeaderValue, ORIGIN, VARY,
+    },
+};
+
+/// Origins allowed to make cross-origin requests unless configured otherwise, those the web client
+/// is served from during development.
+pub const CORS_ORIGINS: &[&str] = &["http://localhost:3000", "http://127.0.0.1:3000"];
+/// Methods allowed in cross-origin requests unless configured otherwise.
+pub const CORS_METHODS: &[&str] = &["POST"];
+/// Headers allowed in cross-origin requests unless configured otherwise.
+pub const CORS_HEADERS: &[&str] = &["Content-Type", "Authorization"];
+/// Time browsers may cache the answer to a preflight request, measured in seconds.
+const PREFLIGHT_MAX_AGE_SECS: u32 = 600;
+
+/// Which web pages may make cross-origin BRP requests, and with which methods and headers.
+///
+/// Requests without an `Origin` header don't come from a browser page and aren't subject to
+/// the policy.
+#[derive(Clone, Debug)]
+pub struct CorsPolicy {
+    /// Allowed origins, any origin when `None`
+    origins: Option<Vec<String>>,
+    /// Allowed HTTP methods, in upper case
+    methods: Vec<String>,
+    /// Allowed request headers, in lower case
+    headers: Vec<String>,
+}
+
+impl CorsPolicy {
+    pub fn new(origins: Option<Vec<String>>, methods: Vec<String>, headers: Vec<String>) -> Self {
+        Self {
+            origins: origins.map(|origins| {
+                origins
+                    .into_iter()
+                    .map(|origin| origin.to_ascii_lowercase())
+                    .collect()
+            }),
+            methods: methods
+                .into_iter()
+                .map(|method| method.to_ascii_uppercase())
+                .collect(),
+            headers: headers
+                .into_iter()
+                .map(|header| header.to_ascii_lowercase())
+                .collect(),
+        }
+    }
+
+    /// Whether a request coming with these headers may be processed.
+    pub fn allows(&self, request_headers: &HeaderMap) -> bool {
+        match request_headers.get(ORIGIN) {
+            Some(origin) => self.allows_origin(origin),
+            None => true,
+        }
+    }
+
+    fn allows_origin(&self, origin: &HeaderValue) -> bool {
+        let Some(origins) = &self.origins else {
+            return true;
+        };
+
+        origin.to_str().is_ok_and(|origin| {
+            origins
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(origin))
+        })
+    }
+
+    /// Headers letting the page the request came from read the response.
+    pub fn response_headers(&self, request_headers: &HeaderMap) -> HeaderMap {
+        let mut headers = HeaderMap::default();
+        let Some(origin) = request_headers.get(ORIGIN) else {
+            return headers;
+        };
+
+        if self.origins.is_none() {
+            headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+        } else if self.allows_origin(origin) {
+            headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone());
+            // The response depends on the origin, caches must not serve it to other origins.
+            headers.insert(VARY, HeaderValue::from_static("Origin"));
+        }
+
+        headers
+    }
+
+    /// Headers answering a preflight request, or `None` when the request it announces isn't
+    /// allowed.
+    pub fn preflight_headers(&self, request_headers: &HeaderMap) -> Option<HeaderMap> {
+        let origin = request_headers.get(ORIGIN)?;
+        let method = request_headers
+            .get(ACCESS_CONTROL_REQUEST_METHOD)?
+            .to_str()
+            .ok()?;
+        let requested_headers = match request_headers.get(ACCESS_CONTROL_REQUEST_HEADERS) {
+            Some(value) => value.to_str().ok()?,
+            None => "",
+        };
+        let headers_allowed = requested_headers
+            .split(',')
+            .map(str::trim)
+            .filter(|header| !header.is_empty())
+            .all(|header| {
+                self.headers
+                    .iter()
+                    .any(|allowed| allowed.eq_ignore_ascii_case(header))
+            });
+
+        if !self.allows_origin(origin)
+            || !self.methods.iter().any(|allowed| allowed == method)
+            || !headers_allowed
+        {
+            return None;
+        }
+
+        let mut headers = self.response_headers(request_headers);
+
+        headers.insert(
+            ACCESS_CONTROL_ALLOW_METHODS,
+            HeaderValue::from_str(&self.methods.join(", ")).ok()?,
+        );
+        headers.insert(
+            ACCESS_CONTROL_ALLOW_HEADERS,
+            HeaderValue::from_str(&self.headers.join(", ")).ok()?,
+        );
+        headers.insert(ACCESS_CONTROL_MAX_AGE, PREFLIGHT_MAX_AGE_SECS.into());
+
+        Some(headers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DASHBOARD: &str = "https://dashboard.intranet";
+
+    fn new_policy(origins: Option<&[&str]>) -> CorsPolicy {
+        CorsPolicy::new(
+            origins.map(|origins| origins.iter().map(ToString::to_string).collect()),
+            vec!["post".to_string()],
+            vec!["Content-Type".to_string(), "Authorization".to_string()],
+        )
+    }
+
+    fn request(headers: &[(&'static str, &'static str)]) -> HeaderMap {
+        headers
+            .iter()
+            .map(|(name, value)| (name.parse().unwrap(), HeaderValue::from_static(value)))
+            .collect()
+    }
+
+    #[test]
+    fn only_listed_origins_are_allowed() {
+        let policy = new_policy(Some(&[DASHBOARD]));
+        let allowed = request(&[("origin", DASHBOARD)]);
+        let other = request(&[("origin", "https://evil.example")]);
+
+        assert!(policy.allows(&allowed));
+        assert!(!policy.allows(&other));
+        assert!(policy.allows(&HeaderMap::default()));
+        assert_eq!(
+            policy.response_headers(&allowed)[ACCESS_CONTROL_ALLOW_ORIGIN],
+            DASHBOARD
+        );
+        assert!(policy.response_headers(&other).is_empty());
+        assert_eq!(
+            new_policy(None).response_headers(&other)[ACCESS_CONTROL_ALLOW_ORIGIN],
+            "*"
+        );
+    }
+
+    #[test]
+    fn preflight_checks_the_method_and_headers() {
+        let policy = new_policy(Some(&[DASHBOARD]));
+        let allowed = request(&[
+            ("origin", DASHBOARD),
+            ("access-control-request-method", "POST"),
+            (
+                "access-control-request-headers",
+                "content-type, authorization",
+            ),
+        ]);
+        let headers = policy.preflight_headers(&allowed).unwrap();
+
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_ORIGIN], DASHBOARD);
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_METHODS], "POST");
+        assert_eq!(
+            headers[ACCESS_CONTROL_ALLOW_HEADERS],
+            "content-type, authorization"
+        );
+
+        for refused in [
+            request(&[
+                ("origin", DASHBOARD),
+                ("access-control-request-method", "DELETE"),
+            ]),
+            request(&[
+                ("origin", DASHBOARD),
+                ("access-control-request-method", "POST"),
+                ("access-control-request-headers", "x-custom"),
+            ]),
+            request(&[
+                ("origin", "https://evil.example"),
+                ("access-control-request-method", "POST"),
+            ]),
+        ] {
+            assert!(policy.preflight_headers(&refused).is_none());
+        }
+    }
+}
@@ -0,0 +1,451 @@
+use core::{
+    convert::Infallible,
+    net::IpAddr,
+    pin::Pin,
+    sync::atomic::{AtomicU64, Ordering},
+    task::{Context, Poll},
+};
+use std::{
+    net::{TcpListener, TcpStream},
+    sync::Arc,
+};
+
+use async_channel::{Receiver, Sender};
+use async_io::Async;
+use bevy::{
+    prelude::*,
+    remote::{
+        BrpBatch, BrpError, BrpMessage, BrpRequest, BrpResponse, BrpResult, BrpSender,
+        error_codes::INVALID_REQUEST,
+        http::{DEFAULT_ADDR, DEFAULT_PORT},
+    },
+    tasks::{IoTaskPool, futures_lite::StreamExt},
+};
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    HeaderMap, Method, Request, Response, StatusCode,
+    body::{Body, Bytes, Frame, Incoming},
+    header::{ALLOW, AUTHORIZATION, CONTENT_TYPE, HeaderValue, ORIGIN, WWW_AUTHENTICATE},
+    server::conn::http1,
+    service,
+};
+use smol_hyper::rt::{FuturesIo, SmolTimer};
+
+use crate::{
+    auth::{AccessTokens, Role, required_role},
+    brp::{
+        STREAM_ID_PARAM,
+        error_codes::{FORBIDDEN, UNAUTHORIZED},
+    },
+    cors::CorsPolicy,
+};
+
+/// Serves BRP over HTTP, like Bevy's `RemoteHttpPlugin`, checking the access token of every
+/// request against the role its methods require, and the origin of browser requests against the
+/// CORS policy.
+///
+/// Clients `POST` JSON-RPC requests to the root URL, with an `Authorization: Bearer <token>`
+/// header when tokens are configured. Without tokens every client is an admin.
+pub struct BrpHttpPlugin {
+    address: IpAddr,
+    port: u16,
+    cors: CorsPolicy,
+    tokens: Option<AccessTokens>,
+}
+
+/// Settings shared by the connections of the server.
+struct ServerSettings {
+    cors: CorsPolicy,
+    tokens: Option<AccessTokens>,
+}
+
+impl Default for BrpHttpPlugin {
+    fn default() -> Self {
+        Self {
+            address: DEFAULT_ADDR,
+            port: DEFAULT_PORT,
+            cors: CorsPolicy::new(Some(Vec::default()), Vec::default(), Vec::default()),
+            tokens: None,
+        }
+    }
+}
+
+impl BrpHttpPlugin {
+    #[must_use]
+    pub fn with_address(mut self, address: IpAddr) -> Self {
+        self.address = address;
+        self
+    }
+
+    #[must_use]
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Pages allowed to make cross-origin requests, none by default.
+    #[must_use]
+    pub fn with_cors(mut self, cors: CorsPolicy) -> Self {
+        self.cors = cors;
+        self
+    }
+
+    /// Tokens clients must authenticate with, every client is an admin when there are none.
+    #[must_use]
+    pub fn with_tokens(mut self, tokens: Option<AccessTokens>) -> Self {
+        self.tokens = tokens;
+        self
+    }
+}
+
+impl Plugin for BrpHttpPlugin {
+    fn build(&self, app: &mut App) {
+        let address = self.address;
+        let port = self.port;
+        let settings = Arc::new(ServerSettings {
+            cors: self.cors.clone(),
+            tokens: self.tokens.clone(),
+        });
+
+        app.add_systems(Startup, move |request_sender: Res<BrpSender>| {
+            let request_sender = (*request_sender).clone();
+            let settings = settings.clone();
+
+            IoTaskPool::get()
+                .spawn(async move {
+                    if let Err(error) = listen(address, port, request_sender, settings).await {
+                        error!("BRP server stopped: {error}");
+                    }
+                })
+                .detach();
+        });
+    }
+}
+
+async fn listen(
+    address: IpAddr,
+    port: u16,
+    request_sender: Sender<BrpMessage>,
+    settings: Arc<ServerSettings>,
+) -> std::io::Result<()> {
+    let listener = Async::<TcpListener>::bind((address, port))?;
+
+    loop {
+        let (client, _) = listener.accept().await?;
+        let request_sender = request_sender.clone();
+        let settings = settings.clone();
+
+        IoTaskPool::get()
+            .spawn(async move {
+                _ = handle_client(client, &request_sender, &settings).await;
+            })
+            .detach();
+    }
+}
+
+async fn handle_client(
+    client: Async<TcpStream>,
+    request_sender: &Sender<BrpMessage>,
+    settings: &ServerSettings,
+) -> hyper::Result<()> {
+    http1::Builder::new()
+        .timer(SmolTimer::new())
+        .serve_connection(
+            FuturesIo::new(client),
+            service::service_fn(|request| process_request(request, request_sender, settings)),
+        )
+        .await
+}
+
+/// Answers an HTTP request, holding a single JSON-RPC request or a batch of them.
+async fn process_request(
+    request: Request<Incoming>,
+    request_sender: &Sender<BrpMessage>,
+    settings: &ServerSettings,
+) -> hyper::Result<Response<HttpBody>> {
+    // Preflight requests carry no credentials, they only ask whether the request is allowed.
+    if request.method() == Method::OPTIONS {
+        if !request.headers().contains_key(ORIGIN) {
+            let mut response = response(
+                StatusCode::NO_CONTENT,
+                HttpBody::empty(),
+                HeaderMap::default(),
+            );
+
+            response
+                .headers_mut()
+                .insert(ALLOW, HeaderValue::from_static("POST, OPTIONS"));
+            return Ok(response);
+        }
+
+        return Ok(match settings.cors.preflight_headers(request.headers()) {
+            Some(headers) => response(StatusCode::NO_CONTENT, HttpBody::empty(), headers),
+            None => response(
+                StatusCode::FORBIDDEN,
+                HttpBody::empty(),
+                HeaderMap::default(),
+            ),
+        });
+    }
+
+    // Browsers only keep pages from reading the response, the request itself must be refused.
+    if !settings.cors.allows(request.headers()) {
+        return Ok(response(
+            StatusCode::FORBIDDEN,
+            HttpBody::empty(),
+            HeaderMap::default(),
+        ));
+    }
+
+    let cors_headers = settings.cors.response_headers(request.headers());
+
+    let role = match &settings.tokens {
+        Some(tokens) => request
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .and_then(|token| tokens.find(token.trim()))
+            .map(|token| token.role),
+        None => Some(Role::Admin),
+    };
+    let Some(role) = role else {
+        let mut response = response(
+            StatusCode::UNAUTHORIZED,
+            HttpBody::json(&BrpResponse::new(
+                None,
+                Err(BrpError {
+                    code: UNAUTHORIZED,
+                    message: "Missing or unknown access token".to_string(),
+                    data: None,
+                }),
+            )),
+            cors_headers,
+        );
+
+        response
+            .headers_mut()
+            .insert(WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+        return Ok(response);
+    };
+
+    let batch_bytes = request.into_body().collect().await?.to_bytes();
+    let body = match serde_json::from_slice::<BrpBatch>(&batch_bytes) {
+        Ok(BrpBatch::Single(request)) => {
+            match process_single_request(request, role, request_sender).await {
+                Reply::Complete(response) => HttpBody::json(&response),
+                Reply::Stream(stream) => HttpBody::Stream(stream),
+            }
+        }
+        Ok(BrpBatch::Batch(requests)) => {
+            let mut responses = Vec::default();
+
+            for request in requests {
+                responses.push(
+                    match process_single_request(request, role, request_sender).await {
+                        Reply::Complete(response) => response,
+                        Reply::Stream(stream) => BrpResponse::new(
+                            stream.id,
+                            Err(BrpError {
+                                code: INVALID_REQUEST,
+                                message: "Streaming can not be used in batch requests".to_string(),
+                                data: None,
+                            }),
+                        ),
+                    },
+                );
+            }
+
+            HttpBody::json(&responses)
+        }
+        Err(error) => HttpBody::json(&BrpResponse::new(
+            None,
+            Err(BrpError {
+                code: INVALID_REQUEST,
+                message: error.to_string(),
+                data: None,
+            }),
+        )),
+    };
+
+    Ok(response(StatusCode::OK, body, cors_headers))
+}
+
+/// Sends a request to the world, once its method is known to be allowed for `role`.
+async fn process_single_request(
+    request: serde_json::Value,
+    role: Role,
+    request_sender: &Sender<BrpMessage>,
+) -> Reply {
+    // Reach in and get the request ID early so that we can report it even when parsing fails.
+    let id = request.as_object().and_then(|map| map.get("id")).cloned();
+    let error = |code, message| {
+        Reply::Complete(BrpResponse::new(
+            id.clone(),
+            Err(BrpError {
+                code,
+                message,
+                data: None,
+            }),
+        ))
+    };
+
+    let request = match serde_json::from_value::<BrpRequest>(request) {
+        Ok(request) => request,
+        Err(err) => return error(INVALID_REQUEST, err.to_string()),
+    };
+
+    if request.jsonrpc != "2.0" {
+        return error(
+            INVALID_REQUEST,
+            "JSON-RPC request requires `\"jsonrpc\": \"2.0\"`".to_string(),
+        );
+    }
+
+    if role < required_role(&request.method, request.params.as_ref()) {
+        return error(
+            FORBIDDEN,
+            format!("{}: Not allowed for the {role:?} role", request.method),
+        );
+    }
+
+    let watch = request.method.contains("+watch");
+    let (result_sender, result_receiver) = async_channel::bounded(if watch { 8 } else { 1 });
+    let params = match watch {
+        true => with_stream_id(request.params),
+        false => request.params,
+    };
+
+    _ = request_sender
+        .send(BrpMessage {
+            method: request.method,
+            params,
+            sender: result_sender,
+        })
+        .await;
+
+    if watch {
+        return Reply::Stream(BrpStream {
+            id: request.id,
+            rx: Box::pin(result_receiver),
+        });
+    }
+
+    let result = result_receiver.recv().await.unwrap_or_else(|_| {
+        Err(BrpError::internal(
+            "The request was dropped without a response",
+        ))
+    });
+
+    Reply::Complete(BrpResponse::new(request.id, result))
+}
+
+/// Adds an id unique to the request to the params of a watching request, replacing any the client
+/// sent, see [`STREAM_ID_PARAM`].
+fn with_stream_id(params: Option<serde_json::Value>) -> Option<serde_json::Value> {
+    static NEXT_STREAM_ID: AtomicU64 = AtomicU64::new(0);
+
+    let stream_id = NEXT_STREAM_ID.fetch_add(1, Ordering::Relaxed);
+
+    match params {
+        None => Some(serde_json::json!({ STREAM_ID_PARAM: stream_id })),
+        Some(serde_json::Value::Object(mut params)) => {
+            params.insert(STREAM_ID_PARAM.to_string(), stream_id.into());
+            Some(serde_json::Value::Object(params))
+        }
+        params => params,
+    }
+}
+
+fn response(status: StatusCode, body: HttpBody, headers: HeaderMap) -> Response<HttpBody> {
+    let content_type = match &body {
+        HttpBody::Complete(_) => "application/json",
+        HttpBody::Stream(_) => "text/event-stream",
+    };
+    let has_content = !body.is_end_stream();
+    let mut response = Response::new(body);
+
+    *response.status_mut() = status;
+
+    if has_content {
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+    }
+
+    response.headers_mut().extend(headers);
+    response
+}
+
+enum Reply {
+    Complete(BrpResponse),
+    Stream(BrpStream),
+}
+
+/// Results of a watching request, sent as server-sent events.
+struct BrpStream {
+    id: Option<serde_json::Value>,
+    rx: Pin<Box<Receiver<BrpResult>>>,
+}
+
+impl Body for BrpStream {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        self.rx.poll_next(cx).map(|result| {
+            result.map(|result| {
+                let response = BrpResponse::new(self.id.clone(), result);
+                let serialized = serde_json::to_string(&response).unwrap_or_default();
+
+                Ok(Frame::data(Bytes::from(format!("data: {serialized}\n\n"))))
+            })
+        })
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.rx.is_closed()
+    }
+}
+
+enum HttpBody {
+    Complete(Full<Bytes>),
+    Stream(BrpStream),
+}
+
+impl HttpBody {
+    fn empty() -> Self {
+        Self::Complete(Full::default())
+    }
+
+    fn json(value: &impl serde::Serialize) -> Self {
+        Self::Complete(Full::new(Bytes::from(
+            serde_json::to_vec(value).unwrap_or_default(),
+        )))
+    }
+}
+
+impl Body for HttpBody {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        match self.get_mut() {
+            Self::Complete(body) => Pin::new(body).poll_frame(cx),
+            Self::Stream(body) => Pin::new(body).poll_frame(cx),
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        match self {
+            Self::Complete(body) => body.is_end_stream(),
+            Self::Stream(body) => body.is_end_stream(),
+        }
+    }
+}
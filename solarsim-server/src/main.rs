@@ -1,49 +1,335 @@
-use bevy::{
-    input_focus::{InputDispatchPlugin, tab_navigation::TabNavigationPlugin},
-    prelude::*,
-    remote::{
-        RemotePlugin,
-        http::{Headers, RemoteHttpPlugin},
+use core::time::Duration;
+use std::path::PathBuf;
+
+use bevy::{app::ScheduleRunnerPlugin, log::LogPlugin, prelude::*, remote::RemotePlugin};
+use clap::{CommandFactory, Parser, error::ErrorKind};
+
+use crate::{
+    auth::AccessTokens,
+    brp::{
+        simulation_create_session, simulation_describe, simulation_destroy_session,
+        simulation_get_history, simulation_get_state, simulation_get_state_watch,
+        simulation_history, simulation_list_sessions, simulation_load_scenario, simulation_pause,
+        simulation_resume, simulation_rewind, simulation_save_scenario, simulation_set_clock,
+        simulation_set_speed, simulation_step, simulation_update_field, simulation_update_fields,
+        world_insert_resources, world_mutate_resources, world_remove_resources,
+    },
+    cli::{Cli, Command, ServerOptions},
+    cors::CorsPolicy,
+    http::BrpHttpPlugin,
+    sessions::SimulationSessions,
+    simulation::{
+        ActiveScenario, CloudCover, FieldSeries, HorizonProfile, InputRecorder, InputRecording,
+        SCENARIO_VERSION, Scenario, SimulationHistory, SimulationRunner, SimulationSnapshot,
+        SolarIrradianceDriver,
     },
-    ui_widgets::UiWidgetsPlugins,
 };
 
-use crate::brp::simulation_update_field;
-
+mod auth;
+mod batch;
 mod brp;
+mod cli;
+mod cors;
+mod http;
+mod monte_carlo;
+mod optimize;
+mod sessions;
 mod simulation;
+mod sweep;
+#[cfg(feature = "gui")]
 mod ui;
 mod utils;
 
 const FIXED_TIMESTEP_SECS: f64 = 0.5;
+/// Rate at which the app updates when there is no window driving it.
+const HEADLESS_UPDATE_RATE_HZ: f64 = 60.0;
+/// Environment variable holding the path to a horizon profile CSV file.
+const HORIZON_PROFILE_ENV: &str = "SOLARSIM_HORIZON_PROFILE";
+/// Environment variable holding the seed of the cloud-cover generator that drives irradiance.
+const CLOUD_COVER_SEED_ENV: &str = "SOLARSIM_CLOUD_COVER_SEED";
 
 fn main() {
+    let cli = Cli::parse();
+    let options = cli
+        .resolve_options()
+        .unwrap_or_else(|error| Cli::command().error(ErrorKind::Io, error).exit());
+
+    if cli.command.is_some() {
+        // Only sets up logging, the subcommands don't run the app.
+        App::new().add_plugins(log_plugin(&options));
+    }
+
+    match &cli.command {
+        Some(Command::Batch(args)) => {
+            let replay = match args.replay.as_ref().map(InputRecording::load).transpose() {
+                Ok(replay) => replay,
+                Err(error) => {
+                    error!("{error}");
+                    std::process::exit(1);
+                }
+            };
+            let runner = match &replay {
+                Some(recording) => recording.initial_state.runner(),
+                None => initial_runner(&options),
+            };
+
+            if let Err(error) = batch::run(runner, args, replay.as_ref()) {
+                error!("{error}");
+                std::process::exit(1);
+            }
+
+            return;
+        }
+        Some(Command::Sweep(args)) => {
+            if let Err(error) = sweep::run(&initial_snapshot(&options), args) {
+                error!("{error}");
+                std::process::exit(1);
+            }
+
+            return;
+        }
+        Some(Command::Optimize(args)) => {
+            if let Err(error) = optimize::run(&initial_snapshot(&options), args) {
+                error!("{error}");
+                std::process::exit(1);
+            }
+
+            return;
+        }
+        Some(Command::MonteCarlo(args)) => {
+            if let Err(error) = monte_carlo::run(&initial_snapshot(&options), args) {
+                error!("{error}");
+                std::process::exit(1);
+            }
+
+            return;
+        }
+        Some(Command::UpgradeScenarios { paths }) => {
+            if !upgrade_scenarios(paths) {
+                std::process::exit(1);
+            }
+
+            return;
+        }
+        None => {}
+    }
+
+    let tokens = options
+        .tokens
+        .as_deref()
+        .map(AccessTokens::load)
+        .transpose()
+        .unwrap_or_else(|error| Cli::command().error(ErrorKind::Io, error).exit());
     let mut app = App::new();
 
-    let cors_headers = Headers::new()
-        .insert("Access-Control-Allow-Origin", "*")
-        .insert("Access-Control-Allow-Headers", "Content-Type");
+    if options.headless() {
+        add_headless_plugins(&mut app, &options);
+    } else {
+        #[cfg(feature = "gui")]
+        add_gui_plugins(&mut app, &options);
+    }
+
+    app.add_plugins((
+        // Replaces the built-in methods, to record the changes they make to the simulation or
+        // refuse those that can't be recorded.
+        RemotePlugin::default()
+            .with_method("world.insert_resources", world_insert_resources)
+            .with_method("world.mutate_resources", world_mutate_resources)
+            .with_method("world.remove_resources", world_remove_resources)
+            .with_method("simulation.update_field", simulation_update_field)
+            .with_method("simulation.update_fields", simulation_update_fields)
+            .with_method("simulation.pause", simulation_pause)
+            .with_method("simulation.resume", simulation_resume)
+            .with_method("simulation.step", simulation_step)
+            .with_method("simulation.set_speed", simulation_set_speed)
+            .with_method("simulation.set_clock", simulation_set_clock)
+            .with_method("simulation.save_scenario", simulation_save_scenario)
+            .with_method("simulation.load_scenario", simulation_load_scenario)
+            .with_method("simulation.create_session", simulation_create_session)
+            .with_method("simulation.list_sessions", simulation_list_sessions)
+            .with_method("simulation.destroy_session", simulation_destroy_session)
+            .with_method("simulation.history", simulation_history)
+            .with_method("simulation.rewind", simulation_rewind)
+            .with_method("simulation.describe", simulation_describe)
+            .with_method("simulation.get_state", simulation_get_state)
+            .with_method("simulation.get_history", simulation_get_history)
+            .with_watching_method("simulation.get_state+watch", simulation_get_state_watch),
+        BrpHttpPlugin::default()
+            .with_address(options.address())
+            .with_port(options.port())
+            .with_cors(cors_policy(&options))
+            .with_tokens(tokens.clone()),
+    ));
+
+    if tokens.is_none() && !options.address().is_loopback() {
+        warn!(
+            "BRP is reachable from the network without access tokens, pass --tokens to require them"
+        );
+    }
+
+    app.add_plugins((simulation::plugin, sessions::plugin));
+
+    let mut sessions = app
+        .world_mut()
+        .non_send_resource_mut::<SimulationSessions>();
+
+    sessions.set_idle_timeout(Duration::from_secs_f64(options.session_timeout_secs()));
+    sessions.set_max_sessions(options.max_sessions());
+
+    insert_environment_resources(&mut app);
+    insert_scenario(&mut app, &options);
+
+    app.insert_resource(SimulationHistory::new(
+        options.history_interval(),
+        options.history_budget_bytes(),
+    ));
+    app.insert_resource(FieldSeries::new(
+        options.series_interval_secs(),
+        options.series_retention_secs(),
+    ));
+    app.insert_resource(Time::<Fixed>::from_seconds(options.timestep_secs()));
+
+    if let Some(path) = &options.record {
+        match InputRecorder::create(path) {
+            Ok(recorder) => {
+                app.insert_resource(recorder);
+            }
+            Err(error) => {
+                error!("Unable to record to {}: {error}", path.display());
+                std::process::exit(1);
+            }
+        }
+    }
+
+    app.run();
+}
+
+/// Runner for the batch subcommands, set up like the server from the environment and scenario.
+fn initial_runner(options: &ServerOptions) -> SimulationRunner {
+    let mut runner = SimulationRunner::new(options.timestep_secs());
+
+    insert_environment_resources(runner.app_mut());
+    insert_scenario(runner.app_mut(), options);
+
+    runner
+}
+
+/// State the sweep, optimize and Monte Carlo runs start from.
+fn initial_snapshot(options: &ServerOptions) -> SimulationSnapshot {
+    SimulationSnapshot::capture(initial_runner(options).world())
+}
+
+/// Inserts the simulation resources configured through environment variables.
+fn insert_environment_resources(app: &mut App) {
+    if let Ok(path) = std::env::var(HORIZON_PROFILE_ENV) {
+        match HorizonProfile::load(&path) {
+            Ok(profile) => {
+                app.insert_resource(profile);
+            }
+            Err(error) => error!("{path}: {error}"),
+        }
+    }
+
+    if let Ok(seed) = std::env::var(CLOUD_COVER_SEED_ENV) {
+        match seed.parse() {
+            Ok(seed) => {
+                app.insert_resource(SolarIrradianceDriver::CloudCover(CloudCover::new(seed)));
+            }
+            Err(error) => error!("{CLOUD_COVER_SEED_ENV}: {error}"),
+        }
+    }
+}
+
+/// Starts from the scenario passed on the command line, exiting if it can't be loaded.
+fn insert_scenario(app: &mut App, options: &ServerOptions) {
+    let Some(path) = &options.scenario else {
+        return;
+    };
+
+    match Scenario::load(path) {
+        Ok(scenario) => {
+            app.insert_resource(scenario.config);
+            app.insert_resource(ActiveScenario {
+                name: scenario.name,
+            });
+        }
+        Err(error) => {
+            error!("Unable to load scenario: {error}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Rewrites each scenario file in the current format, returning whether all of them succeeded.
+fn upgrade_scenarios(paths: &[PathBuf]) -> bool {
+    let mut succeeded = true;
+
+    for path in paths {
+        match Scenario::load(path).and_then(|scenario| scenario.save(path)) {
+            Ok(()) => info!("{}: upgraded to version {SCENARIO_VERSION}", path.display()),
+            Err(error) => {
+                error!("{error}");
+                succeeded = false;
+            }
+        }
+    }
+
+    succeeded
+}
+
+fn log_plugin(options: &ServerOptions) -> LogPlugin {
+    LogPlugin {
+        level: options.log_level(),
+        ..default()
+    }
+}
+
+/// Policy allowing the configured origins to make cross-origin BRP requests.
+fn cors_policy(options: &ServerOptions) -> CorsPolicy {
+    let origins = options.cors_origins();
+
+    if origins.is_none() {
+        warn!(
+            "Cross-origin BRP requests are allowed from any origin, only use --cors-any-origin during development"
+        );
+    }
+
+    CorsPolicy::new(origins, options.cors_methods(), options.cors_headers())
+}
+
+fn add_headless_plugins(app: &mut App, options: &ServerOptions) {
+    app.add_plugins((
+        MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(
+            1.0 / HEADLESS_UPDATE_RATE_HZ,
+        ))),
+        log_plugin(options),
+    ));
+}
+
+#[cfg(feature = "gui")]
+fn add_gui_plugins(app: &mut App, options: &ServerOptions) {
+    use bevy::{
+        input_focus::{InputDispatchPlugin, tab_navigation::TabNavigationPlugin},
+        ui_widgets::UiWidgetsPlugins,
+    };
 
     app.add_plugins((
-        DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                resizable: false,
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: Some(Window {
+                    resizable: false,
+                    resolution: options.window_size().into(),
+                    ..default()
+                }),
                 ..default()
-            }),
-            ..default()
-        }),
+            })
+            .set(log_plugin(options)),
         UiWidgetsPlugins,
         InputDispatchPlugin,
         TabNavigationPlugin,
-        RemotePlugin::default().with_method("simulation.update_field", simulation_update_field),
-        RemoteHttpPlugin::default().with_headers(cors_headers),
     ));
 
     app.insert_resource(ClearColor(Color::linear_rgb(0.017, 0.017, 0.019)));
 
-    app.add_plugins((simulation::plugin, ui::plugin));
-
-    app.insert_resource(Time::<Fixed>::from_seconds(FIXED_TIMESTEP_SECS));
-
-    app.run();
+    app.add_plugins(ui::plugin);
 }
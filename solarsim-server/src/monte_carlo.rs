@@ -0,0 +1,265 @@
+use std::{collections::BTreeMap, io, time::Instant};
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    batch::{BatchStats, write_output},
+    cli::{BatchFormat, Distribution, MonteCarloArgs},
+    simulation::{SimulationConfig, SimulationSnapshot},
+    sweep::simulate,
+    utils::{Rng, default_jobs, parallel_map},
+};
+
+#[derive(Debug)]
+pub enum MonteCarloError {
+    UnknownField(String),
+    /// The results could not be written.
+    Output(io::Error),
+}
+
+impl core::fmt::Display for MonteCarloError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnknownField(field) => write!(f, "Unknown simulation field {field}"),
+            Self::Output(error) => write!(f, "Unable to write results: {error}"),
+        }
+    }
+}
+
+impl core::error::Error for MonteCarloError {}
+
+/// Spread of a result over every run.
+///
+/// The `p` values follow the exceedance convention of energy yield assessments: `p90` is the
+/// value exceeded in 90% of the runs, i.e. the 10th percentile.
+#[derive(Serialize, Debug, PartialEq)]
+struct Summary {
+    mean: f64,
+    std_dev: f64,
+    p90: f64,
+    p50: f64,
+    p10: f64,
+}
+
+#[derive(Serialize)]
+struct MonteCarloRun {
+    /// Value drawn for every uncertain field
+    fields: BTreeMap<String, f32>,
+    stats: BatchStats,
+}
+
+#[derive(Serialize)]
+struct MonteCarloResults {
+    seed: u64,
+    /// Summary of every energy total
+    summary: BTreeMap<String, Summary>,
+    runs: Vec<MonteCarloRun>,
+}
+
+impl Distribution {
+    fn sample(&self, rng: &mut Rng) -> f64 {
+        match *self {
+            Self::Normal { mean, std_dev } => rng.normal(mean, std_dev),
+            Self::Uniform { min, max } => rng.uniform(min, max),
+            Self::Triangular { min, mode, max } => rng.triangular(min, mode, max),
+        }
+    }
+}
+
+impl Summary {
+    fn new(mut values: Vec<f64>) -> Self {
+        values.sort_by(f64::total_cmp);
+
+        let count = values.len().max(1) as f64;
+        let mean = values.iter().sum::<f64>() / count;
+        let variance = values
+            .iter()
+            .map(|value| (value - mean).powi(2))
+            .sum::<f64>()
+            / count;
+
+        Self {
+            mean,
+            std_dev: variance.sqrt(),
+            p90: quantile(&values, 0.1),
+            p50: quantile(&values, 0.5),
+            p10: quantile(&values, 0.9),
+        }
+    }
+}
+
+/// Runs the simulation from `snapshot` with field values drawn from their distributions, and
+/// writes the spread of the energy totals.
+pub fn run(snapshot: &SimulationSnapshot, args: &MonteCarloArgs) -> Result<(), MonteCarloError> {
+    let samples = draw(&snapshot.config, args)?;
+    let jobs = args.jobs.unwrap_or_else(default_jobs);
+    let started_at = Instant::now();
+    let stats = parallel_map(&samples, jobs, |fields| {
+        BatchStats::new(&simulate(snapshot, args.duration, fields).1)
+    });
+
+    info!(
+        "Simulated {} runs of {}s on {jobs} threads in {:.2?}",
+        samples.len(),
+        args.duration,
+        started_at.elapsed()
+    );
+
+    let mut metrics = BTreeMap::<&str, Vec<f64>>::default();
+
+    for stats in &stats {
+        for (name, value) in stats.metrics() {
+            metrics.entry(name).or_default().push(value);
+        }
+    }
+
+    let results = MonteCarloResults {
+        seed: args.seed,
+        summary: metrics
+            .into_iter()
+            .map(|(name, values)| (name.to_string(), Summary::new(values)))
+            .collect(),
+        runs: samples
+            .into_iter()
+            .zip(stats)
+            .map(|(fields, stats)| MonteCarloRun {
+                fields: fields.into_iter().collect(),
+                stats,
+            })
+            .collect(),
+    };
+    let output = match args.format {
+        BatchFormat::Csv => Ok(to_csv(&results.summary)),
+        BatchFormat::Json => serde_json::to_string_pretty(&results).map_err(io::Error::other),
+    }
+    .map_err(MonteCarloError::Output)?;
+
+    write_output(args.output.as_deref(), &output).map_err(MonteCarloError::Output)
+}
+
+/// Draws the field values of every run, clamped to the range of their field.
+///
+/// The values are drawn up front from a single generator, so they only depend on the seed and
+/// not on the order the runs are simulated in.
+fn draw(
+    config: &SimulationConfig,
+    args: &MonteCarloArgs,
+) -> Result<Vec<Vec<(String, f32)>>, MonteCarloError> {
+    let ranges = args
+        .uncertainties
+        .iter()
+        .map(|uncertainty| {
+            config
+                .field(&uncertainty.field)
+                .map(|field| (field.min(), field.max()))
+                .ok_or_else(|| MonteCarloError::UnknownField(uncertainty.field.clone()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut rng = Rng::new(args.seed);
+    let mut clamped = 0;
+    let samples = (0..args.runs)
+        .map(|_| {
+            args.uncertainties
+                .iter()
+                .zip(&ranges)
+                .map(|(uncertainty, (min, max))| {
+                    let value = uncertainty.distribution.sample(&mut rng) as f32;
+
+                    if !(min..=max).contains(&&value) {
+                        clamped += 1;
+                    }
+
+                    (uncertainty.field.clone(), value.clamp(*min, *max))
+                })
+                .collect()
+        })
+        .collect();
+
+    if clamped > 0 {
+        warn!("{clamped} drawn values were outside of the range of their field and were clamped");
+    }
+
+    Ok(samples)
+}
+
+/// Linearly interpolated `q` quantile (0.0-1.0) of sorted values.
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let Some(last) = sorted.len().checked_sub(1) else {
+        return 0.0;
+    };
+    let position = q * last as f64;
+    let (lower, upper) = (position.floor() as usize, position.ceil() as usize);
+
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
+
+/// One row per energy total with its statistics.
+fn to_csv(summary: &BTreeMap<String, Summary>) -> String {
+    let mut csv = "metric,mean,std_dev,p90,p50,p10\n".to_string();
+
+    for (name, summary) in summary {
+        let row = [
+            name.clone(),
+            summary.mean.to_string(),
+            summary.std_dev.to_string(),
+            summary.p90.to_string(),
+            summary.p50.to_string(),
+            summary.p10.to_string(),
+        ];
+
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::Uncertainty;
+
+    use super::*;
+
+    fn args(runs: usize, seed: u64) -> MonteCarloArgs {
+        MonteCarloArgs {
+            duration: 3600.0,
+            uncertainties: vec![Uncertainty {
+                field: "panel_efficiency".to_string(),
+                distribution: Distribution::Normal {
+                    mean: 0.5,
+                    std_dev: 1.0,
+                },
+            }],
+            runs,
+            seed,
+            output: None,
+            format: BatchFormat::Csv,
+            jobs: None,
+        }
+    }
+
+    #[test]
+    fn p90_is_exceeded_by_nine_runs_in_ten() {
+        let summary = Summary::new((1..=11).map(f64::from).rev().collect());
+
+        assert_eq!(summary.mean, 6.0);
+        assert_eq!(summary.p90, 2.0);
+        assert_eq!(summary.p50, 6.0);
+        assert_eq!(summary.p10, 10.0);
+    }
+
+    #[test]
+    fn draws_depend_only_on_the_seed_and_stay_in_range() {
+        let config = SimulationConfig::default();
+        let draws = draw(&config, &args(50, 3)).unwrap();
+
+        assert_eq!(draws, draw(&config, &args(50, 3)).unwrap());
+        assert_ne!(draws, draw(&config, &args(50, 4)).unwrap());
+        assert!(
+            draws
+                .iter()
+                .all(|fields| (0.0..=1.0).contains(&fields[0].1))
+        );
+    }
+}
@@ -0,0 +1,478 @@
+use std::{cmp::Ordering, collections::BTreeMap, io, time::Instant};
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    batch::{BatchStats, write_output},
+    cli::{BatchFormat, CostModel, DesignField, Objective, OptimizeArgs},
+    simulation::{SimulationConfig, SimulationSnapshot},
+    sweep::simulate,
+    utils::{default_jobs, parallel_map},
+};
+
+const SECS_PER_YEAR: f64 = 365.0 * 86400.0;
+
+#[derive(Debug)]
+pub enum OptimizeError {
+    UnknownField(String),
+    /// The search range is not within the range of its field.
+    OutOfRange {
+        field: String,
+        min: f32,
+        max: f32,
+    },
+    /// The results could not be written.
+    Output(io::Error),
+}
+
+impl core::fmt::Display for OptimizeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnknownField(field) => write!(f, "Unknown simulation field {field}"),
+            Self::OutOfRange { field, min, max } => {
+                write!(f, "Range of {field} must be within {min}..={max}")
+            }
+            Self::Output(error) => write!(f, "Unable to write results: {error}"),
+        }
+    }
+}
+
+impl core::error::Error for OptimizeError {}
+
+/// Results of a run with one set of field values.
+#[derive(Serialize, Clone)]
+struct Design {
+    /// Value of every optimized field
+    fields: BTreeMap<String, f32>,
+    objective: f64,
+    /// Sum of the amounts the constraints are exceeded by, relative to their limits, zero when
+    /// every constraint is met
+    violation: f64,
+    stats: BatchStats,
+}
+
+/// Best design after an iteration of the search.
+#[derive(Serialize)]
+struct Iteration {
+    iteration: usize,
+    /// Simulation runs so far
+    evaluations: usize,
+    best: Design,
+}
+
+#[derive(Serialize)]
+struct OptimizeResults {
+    objective: Objective,
+    optimum: Design,
+    /// Whether the optimum meets every constraint
+    feasible: bool,
+    history: Vec<Iteration>,
+}
+
+/// Options of a Nelder–Mead search over the unit hypercube.
+struct NelderMead {
+    /// Distance of the initial vertices from the starting point, along each axis
+    initial_step: f64,
+    /// The search stops once every vertex is this close to the best one, along each axis
+    tolerance: f64,
+    max_evaluations: usize,
+}
+
+struct Vertex<S> {
+    point: Vec<f64>,
+    value: S,
+}
+
+impl Design {
+    /// Designs that meet the constraints come first, ordered by objective, followed by the
+    /// others, ordered by how far they are from meeting them.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.violation
+            .total_cmp(&other.violation)
+            .then(self.objective.total_cmp(&other.objective))
+    }
+}
+
+impl CostModel {
+    /// Cost of the collector and tank plus the present value of the auxiliary energy bought over
+    /// the lifetime of the system.
+    fn life_cycle_cost(&self, config: &SimulationConfig, aux_energy_per_year: f64) -> f64 {
+        let value = |name| config.field(name).map_or(0.0, |field| f64::from(**field));
+        let capital =
+            self.panel_cost * value("panel_area") + self.tank_cost * value("tank_water_mass");
+        let present_value_factor = if self.discount_rate == 0.0 {
+            self.lifetime
+        } else {
+            (1.0 - (1.0 + self.discount_rate).powf(-self.lifetime)) / self.discount_rate
+        };
+
+        capital + aux_energy_per_year * self.energy_price * present_value_factor
+    }
+}
+
+/// Searches for the values of the requested fields that minimize the objective while meeting
+/// the constraints, starting from the values in `snapshot`, and writes the convergence history.
+pub fn run(snapshot: &SimulationSnapshot, args: &OptimizeArgs) -> Result<(), OptimizeError> {
+    let ranges = args
+        .fields
+        .iter()
+        .map(|field| range(&snapshot.config, field))
+        .collect::<Result<Vec<_>, _>>()?;
+    let start = ranges
+        .iter()
+        .zip(&args.fields)
+        .map(|((min, max), field)| {
+            let value = snapshot.config.field(&field.field).map_or(*min, |f| **f);
+
+            f64::from((value - min) / (max - min)).clamp(0.0, 1.0)
+        })
+        .collect::<Vec<_>>();
+    let jobs = args.jobs.unwrap_or_else(default_jobs);
+    let evaluate_points = |points: &[Vec<f64>]| {
+        parallel_map(points, jobs, |point| {
+            let fields = args
+                .fields
+                .iter()
+                .zip(&ranges)
+                .zip(point)
+                .map(|((field, (min, max)), x)| {
+                    (field.field.clone(), min + (max - min) * *x as f32)
+                })
+                .collect::<Vec<_>>();
+
+            evaluate(snapshot, args, &fields)
+        })
+    };
+    let options = NelderMead {
+        initial_step: 0.25,
+        tolerance: 1e-3,
+        max_evaluations: args.max_evaluations,
+    };
+    let started_at = Instant::now();
+    let mut history = Vec::default();
+    let optimum = nelder_mead(
+        &start,
+        &options,
+        evaluate_points,
+        Design::cmp,
+        |best, evaluations| {
+            debug!(
+                "Iteration {}: objective {} after {evaluations} runs",
+                history.len(),
+                best.value.objective
+            );
+            history.push(Iteration {
+                iteration: history.len(),
+                evaluations,
+                best: best.value.clone(),
+            });
+        },
+    )
+    .value;
+    let feasible = optimum.violation == 0.0;
+
+    info!(
+        "Found {:?} with {:?} {} after {} iterations in {:.2?}",
+        optimum.fields,
+        args.objective,
+        optimum.objective,
+        history.len(),
+        started_at.elapsed()
+    );
+
+    if !feasible {
+        warn!("No design meets the constraints, the optimum is the closest one found");
+    }
+
+    let results = OptimizeResults {
+        objective: args.objective,
+        optimum,
+        feasible,
+        history,
+    };
+    let output = match args.format {
+        BatchFormat::Csv => to_csv(&args.fields, &results.history),
+        BatchFormat::Json => serde_json::to_string_pretty(&results).map_err(io::Error::other),
+    }
+    .map_err(OptimizeError::Output)?;
+
+    write_output(args.output.as_deref(), &output).map_err(OptimizeError::Output)
+}
+
+/// Range a field is searched in, its requested range or the range of the field.
+fn range(config: &SimulationConfig, field: &DesignField) -> Result<(f32, f32), OptimizeError> {
+    let Some(config_field) = config.field(&field.field) else {
+        return Err(OptimizeError::UnknownField(field.field.clone()));
+    };
+    let (min, max) = (config_field.min(), config_field.max());
+
+    match field.range {
+        None if min < max => Ok((min, max)),
+        Some((low, high)) if min <= low && high <= max => Ok((low, high)),
+        _ => Err(OptimizeError::OutOfRange {
+            field: field.field.clone(),
+            min,
+            max,
+        }),
+    }
+}
+
+/// Runs the simulation with the given field values and scores the result.
+fn evaluate(
+    snapshot: &SimulationSnapshot,
+    args: &OptimizeArgs,
+    fields: &[(String, f32)],
+) -> Design {
+    let (config, stats) = simulate(snapshot, args.duration, fields);
+    let objective = match args.objective {
+        Objective::LifeCycleCost => args
+            .costs
+            .life_cycle_cost(&config, stats.aux_energy * SECS_PER_YEAR / args.duration),
+        Objective::AuxEnergy => stats.aux_energy,
+    };
+    let min_solar_fraction = args.min_solar_fraction.map_or(0.0, |min| {
+        (min - stats.solar_fraction()).max(0.0) / min.max(f64::EPSILON)
+    });
+    let max_overheating_hours = args.max_overheating_hours.map_or(0.0, |max| {
+        (stats.overheating_hours - max).max(0.0) / max.max(1.0)
+    });
+
+    Design {
+        // The simulation clamps some fields, report the values it ran with.
+        fields: fields
+            .iter()
+            .map(|(name, value)| {
+                let value = config.field(name).map_or(*value, |field| **field);
+
+                (name.clone(), value)
+            })
+            .collect(),
+        objective,
+        violation: min_solar_fraction + max_overheating_hours,
+        stats: BatchStats::new(&stats),
+    }
+}
+
+/// Minimizes over the unit hypercube with the Nelder–Mead simplex method, starting from `start`,
+/// and returns the best vertex.
+///
+/// Only the order of the values matters, so `cmp` can rank them on more than one criterion.
+/// `evaluate` receives every point that can be evaluated at once, to spread them across threads.
+/// `on_iteration` is called with the best vertex and the number of evaluations so far.
+fn nelder_mead<S>(
+    start: &[f64],
+    options: &NelderMead,
+    evaluate: impl Fn(&[Vec<f64>]) -> Vec<S>,
+    cmp: impl Fn(&S, &S) -> Ordering,
+    mut on_iteration: impl FnMut(&Vertex<S>, usize),
+) -> Vertex<S> {
+    let n = start.len();
+    let points = (0..=n)
+        .map(|axis| {
+            let mut point = start.to_vec();
+
+            if axis > 0 {
+                let x = &mut point[axis - 1];
+
+                *x += if *x + options.initial_step <= 1.0 {
+                    options.initial_step
+                } else {
+                    -options.initial_step
+                };
+            }
+
+            point
+        })
+        .collect::<Vec<_>>();
+    let mut evaluations = points.len();
+    let values = evaluate(&points);
+    let mut simplex = points
+        .into_iter()
+        .zip(values)
+        .map(|(point, value)| Vertex { point, value })
+        .collect::<Vec<_>>();
+
+    loop {
+        simplex.sort_by(|a, b| cmp(&a.value, &b.value));
+        on_iteration(&simplex[0], evaluations);
+
+        let best = &simplex[0].point;
+        let size = simplex
+            .iter()
+            .flat_map(|vertex| vertex.point.iter().zip(best).map(|(x, b)| (x - b).abs()))
+            .fold(0.0, f64::max);
+
+        if evaluations >= options.max_evaluations || size < options.tolerance {
+            break;
+        }
+
+        let centroid = (0..n)
+            .map(|i| {
+                simplex[..n]
+                    .iter()
+                    .map(|vertex| vertex.point[i])
+                    .sum::<f64>()
+                    / n as f64
+            })
+            .collect::<Vec<_>>();
+        // Point on the line from the centroid through the worst vertex, at `t` times their
+        // distance, kept inside the hypercube.
+        let along = |t: f64| {
+            centroid
+                .iter()
+                .zip(&simplex[n].point)
+                .map(|(c, w)| (c + t * (w - c)).clamp(0.0, 1.0))
+                .collect::<Vec<_>>()
+        };
+        let mut evaluate_one = |point: Vec<f64>| {
+            evaluations += 1;
+
+            let value = evaluate(std::slice::from_ref(&point)).remove(0);
+
+            Vertex { point, value }
+        };
+        let is_better = |a: &Vertex<S>, b: &Vertex<S>| cmp(&a.value, &b.value).is_lt();
+        let reflected = evaluate_one(along(-1.0));
+
+        if is_better(&reflected, &simplex[0]) {
+            let expanded = evaluate_one(along(-2.0));
+
+            simplex[n] = if is_better(&expanded, &reflected) {
+                expanded
+            } else {
+                reflected
+            };
+        } else if is_better(&reflected, &simplex[n - 1]) {
+            simplex[n] = reflected;
+        } else {
+            let outside = is_better(&reflected, &simplex[n]);
+            let contracted = evaluate_one(along(if outside { -0.5 } else { 0.5 }));
+            let threshold = if outside { &reflected } else { &simplex[n] };
+
+            if is_better(&contracted, threshold) {
+                simplex[n] = contracted;
+            } else {
+                let best = simplex[0].point.clone();
+                let points = simplex[1..]
+                    .iter()
+                    .map(|vertex| {
+                        vertex
+                            .point
+                            .iter()
+                            .zip(&best)
+                            .map(|(x, b)| b + 0.5 * (x - b))
+                            .collect()
+                    })
+                    .collect::<Vec<Vec<f64>>>();
+
+                evaluations += points.len();
+
+                for (vertex, (point, value)) in simplex[1..]
+                    .iter_mut()
+                    .zip(points.clone().into_iter().zip(evaluate(&points)))
+                {
+                    *vertex = Vertex { point, value };
+                }
+            }
+        }
+    }
+
+    simplex.swap_remove(0)
+}
+
+/// One row per iteration with the best design so far, the last row is the optimum.
+fn to_csv(fields: &[DesignField], history: &[Iteration]) -> io::Result<String> {
+    let mut csv = String::default();
+
+    for (index, iteration) in history.iter().enumerate() {
+        let stats = iteration.best.stats.metrics();
+
+        if index == 0 {
+            let header = ["iteration", "evaluations", "objective", "violation"]
+                .into_iter()
+                .chain(fields.iter().map(|field| field.field.as_str()))
+                .chain(stats.keys().copied())
+                .collect::<Vec<_>>();
+
+            csv.push_str(&header.join(","));
+            csv.push('\n');
+        }
+
+        let best = &iteration.best;
+        let row = [
+            iteration.iteration.to_string(),
+            iteration.evaluations.to_string(),
+            best.objective.to_string(),
+            best.violation.to_string(),
+        ]
+        .into_iter()
+        .chain(
+            fields
+                .iter()
+                .map(|field| best.fields[&field.field].to_string()),
+        )
+        .chain(stats.values().map(f64::to_string))
+        .collect::<Vec<_>>();
+
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    Ok(csv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nelder_mead_finds_the_minimum_of_a_bowl() {
+        let options = NelderMead {
+            initial_step: 0.25,
+            tolerance: 1e-4,
+            max_evaluations: 500,
+        };
+        let bowl = |points: &[Vec<f64>]| {
+            points
+                .iter()
+                .map(|p| (p[0] - 0.3).powi(2) + 2.0 * (p[1] - 0.8).powi(2))
+                .collect()
+        };
+        let mut iterations = 0;
+        let optimum = nelder_mead(&[0.9, 0.1], &options, bowl, f64::total_cmp, |_, _| {
+            iterations += 1;
+        });
+
+        assert!((optimum.point[0] - 0.3).abs() < 1e-3);
+        assert!((optimum.point[1] - 0.8).abs() < 1e-3);
+        assert!(iterations > 1);
+    }
+
+    #[test]
+    fn search_stays_inside_the_bounds() {
+        let options = NelderMead {
+            initial_step: 0.25,
+            tolerance: 1e-4,
+            max_evaluations: 500,
+        };
+        let slope = |points: &[Vec<f64>]| points.iter().map(|p| -p[0]).collect();
+        let optimum = nelder_mead(&[0.5], &options, slope, f64::total_cmp, |_, _| {});
+
+        assert_eq!(optimum.point, [1.0]);
+    }
+
+    #[test]
+    fn designs_meeting_the_constraints_come_first() {
+        let design = |objective, violation| Design {
+            fields: BTreeMap::default(),
+            objective,
+            violation,
+            stats: BatchStats::new(&default()),
+        };
+
+        assert!(design(100.0, 0.0).cmp(&design(1.0, 0.1)).is_lt());
+        assert!(design(1.0, 0.0).cmp(&design(2.0, 0.0)).is_lt());
+        assert!(design(1.0, 0.2).cmp(&design(1.0, 0.1)).is_gt());
+    }
+}
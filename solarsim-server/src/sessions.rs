@@ -0,0 +1,235 @@
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::simulation::{
+    SimulationClock, SimulationRunState, SimulationRunner, SimulationSnapshot,
+};
+
+/// Time after which sessions that were not accessed are removed, unless configured otherwise.
+pub const SESSION_TIMEOUT_SECS: f64 = 1800.0;
+/// Most sessions running at once, unless configured otherwise.
+pub const MAX_SESSIONS: usize = 8;
+
+pub fn plugin(app: &mut App) {
+    app.insert_non_send_resource(SimulationSessions::new(Duration::from_secs_f64(
+        SESSION_TIMEOUT_SECS,
+    )));
+
+    app.add_systems(FixedUpdate, step_sessions);
+    app.add_systems(Update, remove_idle_sessions);
+}
+
+struct SimulationSession {
+    runner: SimulationRunner,
+    last_active: Instant,
+}
+
+/// Simulations running alongside the main one, each with its own config, clock and stats.
+///
+/// Sessions step along with the main simulation, at the same speed but with their own run
+/// state, and are removed once they have not been accessed for `idle_timeout`. At most
+/// `max_sessions` run at once, as each of them costs memory and step time. This is a non-send
+/// resource, every session holds an [`App`].
+pub struct SimulationSessions {
+    sessions: BTreeMap<String, SimulationSession>,
+    next_id: u64,
+    idle_timeout: Duration,
+    max_sessions: usize,
+}
+
+/// Summary of a session returned by `simulation.list_sessions`.
+#[derive(Serialize)]
+pub struct SessionInfo {
+    pub session: String,
+    /// Local date and time of the session clock
+    pub time: String,
+    pub steps: u64,
+    pub run_state: SimulationRunState,
+    /// Time since the session was last accessed, in seconds
+    pub idle_secs: f64,
+}
+
+impl SimulationSessions {
+    pub fn new(idle_timeout: Duration) -> Self {
+        Self {
+            sessions: BTreeMap::default(),
+            next_id: 0,
+            idle_timeout,
+            max_sessions: MAX_SESSIONS,
+        }
+    }
+
+    /// Starts a session from `snapshot`, returning its id, or `None` if `max_sessions` are
+    /// already running.
+    pub fn create(&mut self, snapshot: &SimulationSnapshot) -> Option<String> {
+        if self.sessions.len() >= self.max_sessions {
+            return None;
+        }
+
+        self.next_id += 1;
+
+        let id = format!("session-{}", self.next_id);
+
+        self.sessions.insert(
+            id.clone(),
+            SimulationSession {
+                runner: snapshot.runner(),
+                last_active: Instant::now(),
+            },
+        );
+
+        Some(id)
+    }
+
+    /// World of the session with the given id, marking the session as active.
+    pub fn world_mut(&mut self, id: &str) -> Option<&mut World> {
+        let session = self.sessions.get_mut(id)?;
+
+        session.last_active = Instant::now();
+
+        Some(session.runner.app_mut().world_mut())
+    }
+
+    /// Removes a session, returning whether it existed.
+    pub fn remove(&mut self, id: &str) -> bool {
+        self.sessions.remove(id).is_some()
+    }
+
+    pub fn list(&self) -> Vec<SessionInfo> {
+        self.sessions
+            .iter()
+            .map(|(id, session)| {
+                let world = session.runner.world();
+                let clock = world.resource::<SimulationClock>();
+
+                SessionInfo {
+                    session: id.clone(),
+                    time: clock.now().to_string(),
+                    steps: clock.steps,
+                    run_state: *world.resource::<SimulationRunState>(),
+                    idle_secs: session.last_active.elapsed().as_secs_f64(),
+                }
+            })
+            .collect()
+    }
+
+    pub fn set_idle_timeout(&mut self, idle_timeout: Duration) {
+        self.idle_timeout = idle_timeout;
+    }
+
+    pub fn max_sessions(&self) -> usize {
+        self.max_sessions
+    }
+
+    pub fn set_max_sessions(&mut self, max_sessions: usize) {
+        self.max_sessions = max_sessions;
+    }
+}
+
+fn step_sessions(mut sessions: NonSendMut<SimulationSessions>) {
+    for session in sessions.sessions.values_mut() {
+        session.runner.step();
+    }
+}
+
+fn remove_idle_sessions(mut sessions: NonSendMut<SimulationSessions>) {
+    let idle_timeout = sessions.idle_timeout;
+
+    sessions.sessions.retain(|id, session| {
+        let active = session.last_active.elapsed() < idle_timeout;
+
+        if !active {
+            info!("Removed idle session {id}");
+        }
+
+        active
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FIXED_TIMESTEP_SECS, simulation::SimulationConfig};
+
+    use super::*;
+
+    fn snapshot() -> SimulationSnapshot {
+        SimulationSnapshot::capture(SimulationRunner::new(FIXED_TIMESTEP_SECS).world())
+    }
+
+    #[test]
+    fn sessions_are_isolated() {
+        let mut sessions = SimulationSessions::new(Duration::from_secs(60));
+        let a = sessions.create(&snapshot()).unwrap();
+        let b = sessions.create(&snapshot()).unwrap();
+
+        sessions
+            .world_mut(&a)
+            .unwrap()
+            .resource_mut::<SimulationConfig>()
+            .set_field("panel_area", 1.0);
+        sessions
+            .world_mut(&b)
+            .unwrap()
+            .insert_resource(SimulationRunState::Paused);
+
+        let mut app = App::new();
+
+        app.insert_non_send_resource(sessions);
+        app.add_systems(Update, step_sessions);
+        app.update();
+
+        let mut sessions = app
+            .world_mut()
+            .non_send_resource_mut::<SimulationSessions>();
+        let panel_area = |world: &World| {
+            world
+                .resource::<SimulationConfig>()
+                .field("panel_area")
+                .map(|field| **field)
+        };
+        let steps = |world: &World| world.resource::<SimulationClock>().steps;
+
+        assert_ne!(a, b);
+        assert_eq!(panel_area(sessions.world_mut(&a).unwrap()), Some(1.0));
+        assert_ne!(panel_area(sessions.world_mut(&b).unwrap()), Some(1.0));
+        assert_eq!(steps(sessions.world_mut(&a).unwrap()), 1);
+        assert_eq!(steps(sessions.world_mut(&b).unwrap()), 0);
+    }
+
+    #[test]
+    fn sessions_are_limited() {
+        let mut sessions = SimulationSessions::new(Duration::from_secs(60));
+
+        sessions.set_max_sessions(2);
+
+        let a = sessions.create(&snapshot()).unwrap();
+
+        assert!(sessions.create(&snapshot()).is_some());
+        assert!(sessions.create(&snapshot()).is_none());
+        assert!(sessions.remove(&a));
+        assert!(sessions.create(&snapshot()).is_some());
+    }
+
+    #[test]
+    fn idle_sessions_are_removed() {
+        let mut app = App::new();
+        let mut sessions = SimulationSessions::new(Duration::ZERO);
+
+        sessions.create(&snapshot());
+        app.insert_non_send_resource(sessions);
+        app.add_systems(Update, remove_idle_sessions);
+        app.update();
+
+        assert!(
+            app.world()
+                .non_send_resource::<SimulationSessions>()
+                .list()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::SolarPosition;
+
+/// Model used to estimate the irradiance on a horizontal surface under a cloudless sky.
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq)]
+#[reflect(Serialize, Deserialize)]
+pub enum ClearSkyModel {
+    /// Haurwitz (1945), only depends on the solar elevation.
+    #[default]
+    Haurwitz,
+    /// ASHRAE clear-sky model, accounts for the seasonal variation of the atmosphere and splits
+    /// beam and diffuse irradiance.
+    Ashrae,
+}
+
+/// Clear-sky irradiance on a horizontal surface.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClearSkyIrradiance {
+    /// Global irradiance, measured in W/m²
+    pub global: f32,
+    /// Fraction of the global irradiance that is diffuse, if the model provides it (0.0-1.0)
+    pub diffuse_fraction: Option<f32>,
+}
+
+impl ClearSkyModel {
+    pub fn irradiance(&self, sun: SolarPosition, day_of_year: f32) -> ClearSkyIrradiance {
+        let sin_elevation = sun.elevation.to_radians().sin();
+
+        if sin_elevation <= 0.0 {
+            return ClearSkyIrradiance {
+                global: 0.0,
+                diffuse_fraction: None,
+            };
+        }
+
+        match self {
+            Self::Haurwitz => ClearSkyIrradiance {
+                global: 1098.0 * sin_elevation * (-0.057 / sin_elevation).exp(),
+                diffuse_fraction: None,
+            },
+            Self::Ashrae => {
+                let season = |offset: f32| (360.0 / 365.0 * (day_of_year - offset)).to_radians();
+                // Apparent extraterrestrial irradiance (W/m²).
+                let a = 1160.0 + 75.0 * season(275.0).sin();
+                // Atmospheric extinction coefficient.
+                let b = 0.174 + 0.035 * season(100.0).sin();
+                // Diffuse radiation factor.
+                let c = 0.095 + 0.04 * season(100.0).sin();
+                let beam_normal = a * (-b / sin_elevation).exp();
+
+                ClearSkyIrradiance {
+                    global: beam_normal * (sin_elevation + c),
+                    diffuse_fraction: Some(c / (sin_elevation + c)),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODELS: [ClearSkyModel; 2] = [ClearSkyModel::Haurwitz, ClearSkyModel::Ashrae];
+
+    #[test]
+    fn no_irradiance_at_night() {
+        let sun = SolarPosition::new(40.0, 172.0, 0.0);
+
+        for model in MODELS {
+            assert_eq!(model.irradiance(sun, 172.0).global, 0.0);
+        }
+    }
+
+    #[test]
+    fn noon_irradiance_is_plausible() {
+        let sun = SolarPosition::new(40.0, 172.0, 12.0);
+
+        for model in MODELS {
+            let global = model.irradiance(sun, 172.0).global;
+
+            assert!((850.0..1100.0).contains(&global), "{model:?}: {global}");
+        }
+    }
+
+    #[test]
+    fn irradiance_peaks_at_noon() {
+        for model in MODELS {
+            let at = |hour| {
+                model
+                    .irradiance(SolarPosition::new(40.0, 172.0, hour), 172.0)
+                    .global
+            };
+
+            assert!(at(12.0) > at(10.0));
+            assert!(at(10.0) > at(7.0));
+        }
+    }
+
+    #[test]
+    fn ashrae_provides_diffuse_fraction() {
+        let sun = SolarPosition::new(40.0, 172.0, 12.0);
+        let irradiance = ClearSkyModel::Ashrae.irradiance(sun, 172.0);
+
+        assert!(
+            irradiance
+                .diffuse_fraction
+                .is_some_and(|f| f > 0.0 && f < 0.5)
+        );
+    }
+}
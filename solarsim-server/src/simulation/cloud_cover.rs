@@ -0,0 +1,222 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::Rng;
+
+use super::ClearSkyModel;
+
+/// Seconds between two consecutive clear-sky index samples.
+const SAMPLE_INTERVAL_SECS: f32 = 60.0;
+
+/// Stochastic clear-sky index generator based on a Markov chain of sky conditions.
+///
+/// Every simulated minute the chain may jump to another sky condition and a new clear-sky index
+/// is drawn around that condition's mean. The resulting index scales a clear-sky envelope to
+/// produce cloud transients. The same seed always produces the same sequence.
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[reflect(Serialize, Deserialize)]
+#[serde(default)]
+pub struct CloudCover {
+    /// Seed used to initialize the random number generator
+    pub seed: u64,
+    /// Model used as clear-sky envelope, `clear_sky_irradiance` is used instead when `None`
+    pub clear_sky_model: Option<ClearSkyModel>,
+    /// Constant irradiance on a cloudless sky, measured in W/m²
+    pub clear_sky_irradiance: f32,
+    /// Sky conditions the Markov chain moves through
+    pub conditions: Vec<SkyCondition>,
+    /// Current state of the chain, it is reset whenever the seed changes.
+    state: Option<CloudCoverState>,
+}
+
+/// A state of the Markov chain.
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[reflect(Serialize, Deserialize)]
+pub struct SkyCondition {
+    /// Mean clear-sky index (ratio between actual and clear-sky irradiance)
+    pub clear_sky_index: f32,
+    /// Standard deviation of the clear-sky index within this condition
+    pub variability: f32,
+    /// Probability of moving to each condition after one minute, must add up to 1.0
+    pub transitions: Vec<f32>,
+}
+
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[reflect(Serialize, Deserialize)]
+struct CloudCoverState {
+    seed: u64,
+    rng: Rng,
+    condition: usize,
+    clear_sky_index: f32,
+    /// Seconds elapsed since the last sample was drawn.
+    elapsed_secs: f32,
+}
+
+impl CloudCover {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, ..default() }
+    }
+
+    /// Advances the chain by `dt` seconds and returns the current clear-sky index.
+    pub fn advance(&mut self, dt: f32) -> f32 {
+        let state = match &mut self.state {
+            Some(state) if state.seed == self.seed => state,
+            state => {
+                let mut rng = Rng::new(self.seed);
+                let clear_sky_index = sample_clear_sky_index(&mut rng, self.conditions.first());
+
+                state.insert(CloudCoverState {
+                    seed: self.seed,
+                    rng,
+                    condition: 0,
+                    clear_sky_index,
+                    elapsed_secs: 0.0,
+                })
+            }
+        };
+
+        state.elapsed_secs += dt;
+
+        while state.elapsed_secs >= SAMPLE_INTERVAL_SECS {
+            state.elapsed_secs -= SAMPLE_INTERVAL_SECS;
+            state.condition = next_condition(&mut state.rng, &self.conditions, state.condition);
+            state.clear_sky_index =
+                sample_clear_sky_index(&mut state.rng, self.conditions.get(state.condition));
+        }
+
+        state.clear_sky_index
+    }
+}
+
+impl Default for CloudCover {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            clear_sky_model: Some(ClearSkyModel::default()),
+            clear_sky_irradiance: 1000.0,
+            conditions: vec![
+                // Clear sky
+                SkyCondition {
+                    clear_sky_index: 0.95,
+                    variability: 0.03,
+                    transitions: vec![0.97, 0.025, 0.005],
+                },
+                // Broken clouds, where most transients happen
+                SkyCondition {
+                    clear_sky_index: 0.6,
+                    variability: 0.25,
+                    transitions: vec![0.05, 0.9, 0.05],
+                },
+                // Overcast
+                SkyCondition {
+                    clear_sky_index: 0.25,
+                    variability: 0.08,
+                    transitions: vec![0.01, 0.04, 0.95],
+                },
+            ],
+            state: None,
+        }
+    }
+}
+
+fn next_condition(rng: &mut Rng, conditions: &[SkyCondition], current: usize) -> usize {
+    let Some(condition) = conditions.get(current) else {
+        return 0;
+    };
+
+    let roll = rng.next_f64() as f32;
+    let mut cumulative = 0.0;
+
+    for (next, probability) in condition.transitions.iter().enumerate() {
+        cumulative += probability;
+
+        if roll < cumulative {
+            return next.min(conditions.len() - 1);
+        }
+    }
+
+    current
+}
+
+fn sample_clear_sky_index(rng: &mut Rng, condition: Option<&SkyCondition>) -> f32 {
+    condition.map_or(1.0, |condition| {
+        // Cloud enhancement can briefly push irradiance above the clear-sky value.
+        rng.normal(
+            f64::from(condition.clear_sky_index),
+            f64::from(condition.variability),
+        )
+        .clamp(0.0, 1.2) as f32
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sequence(cloud_cover: &mut CloudCover, steps: usize) -> Vec<f32> {
+        (0..steps).map(|_| cloud_cover.advance(30.0)).collect()
+    }
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let a = sequence(&mut CloudCover::new(1234), 500);
+        let b = sequence(&mut CloudCover::new(1234), 500);
+
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn different_seeds_diverge() {
+        let a = sequence(&mut CloudCover::new(1), 500);
+        let b = sequence(&mut CloudCover::new(2), 500);
+
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn index_holds_within_a_minute() {
+        let mut cloud_cover = CloudCover::new(5);
+        let first = cloud_cover.advance(0.5);
+
+        for _ in 0..100 {
+            assert_eq!(cloud_cover.advance(0.5), first);
+        }
+    }
+
+    #[test]
+    fn sequence_does_not_depend_on_step_size() {
+        let mut coarse = CloudCover::new(99);
+        let mut fine = CloudCover::new(99);
+
+        for _ in 0..120 {
+            let coarse_index = coarse.advance(60.0);
+            let mut fine_index = 0.0;
+
+            for _ in 0..120 {
+                fine_index = fine.advance(0.5);
+            }
+
+            assert_eq!(coarse_index, fine_index);
+        }
+    }
+
+    #[test]
+    fn changing_the_seed_restarts_the_sequence() {
+        let mut cloud_cover = CloudCover::new(1);
+        let expected = sequence(&mut CloudCover::new(2), 100);
+
+        sequence(&mut cloud_cover, 100);
+        cloud_cover.seed = 2;
+
+        assert_eq!(sequence(&mut cloud_cover, 100), expected);
+    }
+
+    #[test]
+    fn index_stays_within_bounds() {
+        let mut cloud_cover = CloudCover::new(3);
+
+        for index in sequence(&mut cloud_cover, 5000) {
+            assert!((0.0..=1.2).contains(&index));
+        }
+    }
+}
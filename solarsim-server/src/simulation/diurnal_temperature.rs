@@ -0,0 +1,61 @@
+use core::f32::consts::TAU;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Ambient temperature following a sinusoid over the day.
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[reflect(Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiurnalTemperature {
+    /// Lowest temperature of the day, measured in °C
+    pub min: f32,
+    /// Highest temperature of the day, measured in °C
+    pub max: f32,
+    /// Local time of the day when the highest temperature is reached, measured in hours
+    pub peak_hour: f32,
+}
+
+impl DiurnalTemperature {
+    /// Temperature (°C) at the given local time of the day, in hours.
+    pub fn temperature(&self, hour: f32) -> f32 {
+        let mean = (self.max + self.min) / 2.0;
+        let amplitude = (self.max - self.min) / 2.0;
+
+        mean + amplitude * (TAU * (hour - self.peak_hour) / 24.0).cos()
+    }
+}
+
+impl Default for DiurnalTemperature {
+    fn default() -> Self {
+        Self {
+            min: 15.0,
+            max: 28.0,
+            peak_hour: 15.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peaks_at_peak_hour() {
+        let diurnal = DiurnalTemperature::default();
+
+        assert!((diurnal.temperature(15.0) - 28.0).abs() < 1e-4);
+        assert!((diurnal.temperature(3.0) - 15.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn stays_within_min_and_max() {
+        let diurnal = DiurnalTemperature::default();
+
+        for quarter_hour in 0..96 {
+            let temperature = diurnal.temperature(quarter_hour as f32 / 4.0);
+
+            assert!((15.0 - 1e-4..=28.0 + 1e-4).contains(&temperature));
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    ClearSkyModel, CloudCover, DiurnalTemperature, SimulationClock, SimulationConfig,
+    SimulationField, SolarPosition,
+};
+
+/// Source of the `solar_irradiance` value on every simulation step.
+#[derive(Resource, Reflect, Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub enum SolarIrradianceDriver {
+    /// The value is set by the user through the UI or BRP.
+    #[default]
+    Manual,
+    /// The value is produced by a clear-sky model for the current date and location.
+    ClearSky(ClearSkyModel),
+    /// The value is produced by a seeded stochastic cloud-cover generator.
+    CloudCover(CloudCover),
+}
+
+/// Source of the `ambient_temp` value on every simulation step.
+#[derive(Resource, Reflect, Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub enum AmbientTempDriver {
+    /// The value is set by the user through the UI or BRP.
+    #[default]
+    Manual,
+    /// The value follows a sinusoid over the day.
+    Diurnal(DiurnalTemperature),
+}
+
+fn set_clamped(field: &mut SimulationField, value: f32) {
+    **field = value.clamp(field.min(), field.max());
+}
+
+pub(super) fn drive_solar_irradiance(
+    time: Res<Time<Fixed>>,
+    clock: Res<SimulationClock>,
+    mut driver: ResMut<SolarIrradianceDriver>,
+    mut cfg: ResMut<SimulationConfig>,
+) {
+    let (day_of_year, hour) = clock.solar_time(*cfg.longitude);
+
+    let clear_sky = |model: ClearSkyModel, cfg: &mut SimulationConfig| {
+        let sun = SolarPosition::new(*cfg.latitude, day_of_year, hour);
+        let irradiance = model.irradiance(sun, day_of_year);
+
+        set_clamped(&mut cfg.sun_azimuth, sun.azimuth);
+        set_clamped(&mut cfg.sun_elevation, sun.elevation);
+
+        if let Some(diffuse_fraction) = irradiance.diffuse_fraction {
+            set_clamped(&mut cfg.diffuse_fraction, diffuse_fraction);
+        }
+
+        irradiance.global
+    };
+
+    let irradiance = match driver.as_mut() {
+        SolarIrradianceDriver::Manual => return,
+        SolarIrradianceDriver::ClearSky(model) => clear_sky(*model, &mut cfg),
+        SolarIrradianceDriver::CloudCover(cloud_cover) => {
+            let envelope = cloud_cover
+                .clear_sky_model
+                .map_or(cloud_cover.clear_sky_irradiance, |model| {
+                    clear_sky(model, &mut cfg)
+                });
+
+            envelope * cloud_cover.advance(time.delta_secs())
+        }
+    };
+
+    set_clamped(&mut cfg.solar_irradiance, irradiance);
+}
+
+pub(super) fn drive_ambient_temp(
+    clock: Res<SimulationClock>,
+    driver: Res<AmbientTempDriver>,
+    mut cfg: ResMut<SimulationConfig>,
+) {
+    match driver.as_ref() {
+        AmbientTempDriver::Manual => {}
+        AmbientTempDriver::Diurnal(diurnal) => {
+            set_clamped(
+                &mut cfg.ambient_temp,
+                diurnal.temperature(clock.hour_of_day()),
+            );
+        }
+    }
+}
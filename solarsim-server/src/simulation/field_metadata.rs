@@ -0,0 +1,298 @@
+use serde::Serialize;
+
+/// Part of the system a field belongs to, to group the fields of a form.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldGroup {
+    Site,
+    Weather,
+    Collector,
+    Piping,
+    Tank,
+    Load,
+}
+
+/// How a [`SimulationConfig`](super::SimulationConfig) field is presented and edited.
+///
+/// This is the single source of the labels, units and descriptions shown by the window and
+/// returned to clients by `simulation.describe`. The range and kind of a field live on the
+/// field itself, as the range can change while the simulation runs.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct FieldMetadata {
+    pub name: &'static str,
+    pub label: &'static str,
+    /// Unit symbol, empty for dimensionless values
+    pub unit: &'static str,
+    pub description: &'static str,
+    pub group: FieldGroup,
+    /// Increment of the value when stepped through, e.g. with the arrow keys
+    pub step: f64,
+    /// Number of decimal places the value is shown and rounded with
+    pub precision: u8,
+}
+
+/// Metadata of every field, in the order they are presented.
+pub const FIELD_METADATA: &[FieldMetadata] = &[
+    FieldMetadata {
+        name: "latitude",
+        label: "Latitude",
+        unit: "°",
+        description: "Latitude of the site, positive north",
+        group: FieldGroup::Site,
+        step: 0.1,
+        precision: 1,
+    },
+    FieldMetadata {
+        name: "longitude",
+        label: "Longitude",
+        unit: "°",
+        description: "Longitude of the site, positive east",
+        group: FieldGroup::Site,
+        step: 0.1,
+        precision: 1,
+    },
+    FieldMetadata {
+        name: "ambient_temp",
+        label: "Ambient temperature",
+        unit: "°C",
+        description: "Temperature of the air around the collector, pipes and tank",
+        group: FieldGroup::Weather,
+        step: 0.5,
+        precision: 1,
+    },
+    FieldMetadata {
+        name: "solar_irradiance",
+        label: "Solar irradiance",
+        unit: "W/m²",
+        description: "Solar power received per unit of collector area",
+        group: FieldGroup::Weather,
+        step: 10.0,
+        precision: 0,
+    },
+    FieldMetadata {
+        name: "diffuse_fraction",
+        label: "Diffuse fraction",
+        unit: "",
+        description: "Fraction of the solar irradiance that reaches the panel as diffuse sky radiation",
+        group: FieldGroup::Weather,
+        step: 0.01,
+        precision: 2,
+    },
+    FieldMetadata {
+        name: "sun_azimuth",
+        label: "Sun azimuth",
+        unit: "°",
+        description: "Direction of the sun, clockwise from north",
+        group: FieldGroup::Weather,
+        step: 1.0,
+        precision: 0,
+    },
+    FieldMetadata {
+        name: "sun_elevation",
+        label: "Sun elevation",
+        unit: "°",
+        description: "Angle of the sun above the horizontal plane",
+        group: FieldGroup::Weather,
+        step: 1.0,
+        precision: 0,
+    },
+    FieldMetadata {
+        name: "panel_area",
+        label: "Panel area",
+        unit: "m²",
+        description: "Aperture area of the solar panel",
+        group: FieldGroup::Collector,
+        step: 0.1,
+        precision: 1,
+    },
+    FieldMetadata {
+        name: "panel_efficiency",
+        label: "Panel efficiency",
+        unit: "",
+        description: "Share of the irradiance the panel turns into heat (η)",
+        group: FieldGroup::Collector,
+        step: 0.01,
+        precision: 2,
+    },
+    FieldMetadata {
+        name: "panel_heat_loss_coefficient",
+        label: "Panel heat loss coefficient",
+        unit: "W/(m²·K)",
+        description: "Heat the panel loses per unit of loss area and of temperature above ambient",
+        group: FieldGroup::Collector,
+        step: 0.1,
+        precision: 1,
+    },
+    FieldMetadata {
+        name: "panel_loss_area",
+        label: "Panel loss area",
+        unit: "m²",
+        description: "Area of the panel that loses heat to the ambient air",
+        group: FieldGroup::Collector,
+        step: 0.1,
+        precision: 1,
+    },
+    FieldMetadata {
+        name: "shading_loss",
+        label: "Shading loss",
+        unit: "W",
+        description: "Solar power the panel fails to capture due to the horizon profile",
+        group: FieldGroup::Collector,
+        step: 1.0,
+        precision: 0,
+    },
+    FieldMetadata {
+        name: "pipe_outer_surface_area",
+        label: "Pipe surface area",
+        unit: "m²",
+        description: "Outer surface area of the pipes between the panel and the tank",
+        group: FieldGroup::Piping,
+        step: 0.1,
+        precision: 1,
+    },
+    FieldMetadata {
+        name: "pipe_overall_heat_transfer_coefficient",
+        label: "Pipe heat transfer coefficient",
+        unit: "W/(m²·K)",
+        description: "Overall heat transfer coefficient of the pipe insulation",
+        group: FieldGroup::Piping,
+        step: 0.001,
+        precision: 3,
+    },
+    FieldMetadata {
+        name: "pump_flow_rate",
+        label: "Pump flow rate",
+        unit: "m³/s",
+        description: "Volumetric flow rate of the circulation pump",
+        group: FieldGroup::Piping,
+        step: 0.01,
+        precision: 2,
+    },
+    FieldMetadata {
+        name: "water_temp_in",
+        label: "Panel inlet temperature",
+        unit: "°C",
+        description: "Temperature of the water entering the panel",
+        group: FieldGroup::Piping,
+        step: 0.1,
+        precision: 1,
+    },
+    FieldMetadata {
+        name: "tank_surface_area",
+        label: "Tank surface area",
+        unit: "m²",
+        description: "Outer surface area of the storage tank",
+        group: FieldGroup::Tank,
+        step: 0.1,
+        precision: 1,
+    },
+    FieldMetadata {
+        name: "tank_height_diameter_ratio",
+        label: "Tank height to diameter ratio",
+        unit: "",
+        description: "Shape of the storage tank, which sets its capacity with the surface area",
+        group: FieldGroup::Tank,
+        step: 0.1,
+        precision: 1,
+    },
+    FieldMetadata {
+        name: "tank_water_mass",
+        label: "Tank water mass",
+        unit: "kg",
+        description: "Amount of water in the tank, at most the capacity of the tank",
+        group: FieldGroup::Tank,
+        step: 1.0,
+        precision: 0,
+    },
+    FieldMetadata {
+        name: "tank_heat_loss_coefficient",
+        label: "Tank heat loss coefficient",
+        unit: "W/(m²·K)",
+        description: "Heat the tank loses per unit of surface area and of temperature above ambient",
+        group: FieldGroup::Tank,
+        step: 0.1,
+        precision: 1,
+    },
+    FieldMetadata {
+        name: "tank_average_temp",
+        label: "Tank temperature",
+        unit: "°C",
+        description: "Average temperature of the water in the tank",
+        group: FieldGroup::Tank,
+        step: 0.1,
+        precision: 1,
+    },
+    FieldMetadata {
+        name: "load_mass_flow_rate",
+        label: "Load flow rate",
+        unit: "kg/s",
+        description: "Mass flow rate of hot water drawn from the tank by the user",
+        group: FieldGroup::Load,
+        step: 0.01,
+        precision: 2,
+    },
+    FieldMetadata {
+        name: "load_temp",
+        label: "Mains water temperature",
+        unit: "°C",
+        description: "Temperature of the water replacing the water drawn from the tank",
+        group: FieldGroup::Load,
+        step: 0.5,
+        precision: 1,
+    },
+    FieldMetadata {
+        name: "load_delivery_temp",
+        label: "Delivery temperature",
+        unit: "°C",
+        description: "Temperature the load is delivered at, topped up by an auxiliary heater",
+        group: FieldGroup::Load,
+        step: 0.5,
+        precision: 1,
+    },
+];
+
+/// Metadata of the field named `name`.
+#[cfg(any(feature = "gui", test))]
+pub fn field_metadata(name: &str) -> Option<&'static FieldMetadata> {
+    FIELD_METADATA.iter().find(|metadata| metadata.name == name)
+}
+
+impl FieldMetadata {
+    /// Label followed by the unit, if any.
+    #[cfg(feature = "gui")]
+    pub fn label_with_unit(&self) -> String {
+        match self.unit {
+            "" => self.label.to_string(),
+            unit => format!("{} ({unit})", self.label),
+        }
+    }
+
+    /// `value` rounded to the precision of the field.
+    #[cfg(feature = "gui")]
+    pub fn format(&self, value: f32) -> String {
+        format!("{value:.*}", usize::from(self.precision))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::reflect::Struct;
+
+    use crate::simulation::SimulationConfig;
+
+    use super::*;
+
+    #[test]
+    fn every_config_field_has_metadata() {
+        let cfg = SimulationConfig::default();
+        let names = (0..cfg.field_len())
+            .filter_map(|index| cfg.name_at(index))
+            .collect::<Vec<_>>();
+
+        for name in &names {
+            assert!(field_metadata(name).is_some(), "{name} has no metadata");
+        }
+
+        assert_eq!(FIELD_METADATA.len(), names.len());
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use super::{FIELD_METADATA, SimulationClock, SimulationConfig};
+use crate::utils::lttb;
+
+/// Simulated time between two samples of the time series, measured in seconds, unless configured
+/// otherwise.
+pub const SERIES_INTERVAL_SECS: f64 = 60.0;
+/// Simulated time the samples are kept for, measured in seconds, unless configured otherwise.
+pub const SERIES_RETENTION_SECS: f64 = 7.0 * 86400.0;
+
+/// Time series of the value of every field, sampled as the simulation runs.
+///
+/// A sample is taken every `interval_secs` of simulated time, the samples older than
+/// `retention_secs` are dropped. Recording is disabled when the retention is zero, which is the
+/// default so that batch runs don't pay for it.
+#[derive(Resource, Default)]
+pub struct FieldSeries {
+    interval_secs: f64,
+    retention_secs: f64,
+    /// Simulated time of every sample, measured in seconds since the start of the simulation
+    times: VecDeque<f64>,
+    /// Samples of every field, in the order of [`FIELD_METADATA`]
+    values: Vec<VecDeque<f32>>,
+}
+
+impl FieldSeries {
+    pub fn new(interval_secs: f64, retention_secs: f64) -> Self {
+        Self {
+            interval_secs,
+            retention_secs,
+            values: vec![VecDeque::default(); FIELD_METADATA.len()],
+            ..default()
+        }
+    }
+
+    /// An empty time series with the same settings.
+    pub fn empty_copy(&self) -> Self {
+        Self::new(self.interval_secs, self.retention_secs)
+    }
+
+    pub fn interval_secs(&self) -> f64 {
+        self.interval_secs
+    }
+
+    /// Samples of the field named `name` taken between `start` and `end`, reduced to at most
+    /// `max_points` with [`lttb`], or `None` if there is no such field.
+    pub fn query(
+        &self,
+        name: &str,
+        start: f64,
+        end: f64,
+        max_points: Option<usize>,
+    ) -> Option<Vec<(f64, f32)>> {
+        let index = FIELD_METADATA
+            .iter()
+            .position(|metadata| metadata.name == name)?;
+        let first = self.times.partition_point(|time| *time < start);
+        let last = self.times.partition_point(|time| *time <= end);
+        let points = (first..last)
+            .map(|sample| (self.times[sample], self.values[index][sample]))
+            .collect::<Vec<_>>();
+
+        Some(match max_points {
+            Some(max_points) => lttb(&points, max_points),
+            None => points,
+        })
+    }
+
+    fn push(&mut self, time: f64, cfg: &SimulationConfig) {
+        // After a rewind, the samples ahead of the restored state belong to a discarded run.
+        while self.times.back().is_some_and(|last| *last > time) {
+            self.times.pop_back();
+            self.values
+                .iter_mut()
+                .for_each(|values| _ = values.pop_back());
+        }
+
+        if self
+            .times
+            .back()
+            .is_some_and(|last| time - last < self.interval_secs)
+        {
+            return;
+        }
+
+        while self
+            .times
+            .front()
+            .is_some_and(|first| time - first > self.retention_secs)
+        {
+            self.times.pop_front();
+            self.values
+                .iter_mut()
+                .for_each(|values| _ = values.pop_front());
+        }
+
+        self.times.push_back(time);
+
+        for (metadata, values) in FIELD_METADATA.iter().zip(&mut self.values) {
+            values.push_back(cfg.field(metadata.name).map_or(f32::NAN, |field| **field));
+        }
+    }
+}
+
+/// Samples the fields the next step starts from.
+pub(super) fn record_series(
+    clock: Res<SimulationClock>,
+    cfg: Res<SimulationConfig>,
+    mut series: ResMut<FieldSeries>,
+) {
+    if series.retention_secs > 0.0 {
+        series.push(clock.elapsed_secs, &cfg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FIXED_TIMESTEP_SECS, simulation::SimulationRunner};
+
+    use super::*;
+
+    fn times(world: &World) -> Vec<f64> {
+        world
+            .resource::<FieldSeries>()
+            .query("tank_average_temp", 0.0, f64::MAX, None)
+            .unwrap()
+            .into_iter()
+            .map(|(time, _)| time)
+            .collect()
+    }
+
+    #[test]
+    fn samples_are_taken_every_interval_within_retention() {
+        let mut runner = SimulationRunner::new(FIXED_TIMESTEP_SECS);
+
+        runner.app_mut().insert_resource(FieldSeries::new(2.0, 4.0));
+        runner.run(20);
+
+        assert_eq!(times(runner.world()), [4.0, 6.0, 8.0]);
+        assert!(
+            runner
+                .world()
+                .resource::<FieldSeries>()
+                .query("unknown", 0.0, 10.0, None)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn samples_after_a_rewound_time_are_dropped() {
+        let mut series = FieldSeries::new(1.0, 100.0);
+        let cfg = SimulationConfig::default();
+
+        for time in 0..10 {
+            series.push(f64::from(time), &cfg);
+        }
+
+        series.push(4.5, &cfg);
+        series.push(5.0, &cfg);
+
+        assert_eq!(series.times, [0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert!(series.values.iter().all(|values| values.len() == 6));
+    }
+}
@@ -0,0 +1,238 @@
+use std::{fs, io, path::Path};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Obstruction of the sky around the collector, described as the horizon elevation (°) per
+/// azimuth bin.
+///
+/// Azimuths are measured in degrees clockwise from north. Each bin spans from its azimuth up to
+/// the azimuth of the next one, wrapping around at 360°. An empty profile means no obstruction.
+#[derive(Resource, Reflect, Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct HorizonProfile {
+    bins: Vec<HorizonBin>,
+}
+
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[reflect(Serialize, Deserialize)]
+pub struct HorizonBin {
+    /// Azimuth where the bin starts, measured in degrees clockwise from north (0-360)
+    pub azimuth: f32,
+    /// Elevation of the obstruction over the whole bin, measured in degrees (0-90)
+    pub elevation: f32,
+}
+
+#[derive(Debug)]
+pub enum HorizonProfileError {
+    Io(io::Error),
+    /// A line could not be parsed, the line number is 1-based.
+    Parse {
+        line: usize,
+        message: String,
+    },
+}
+
+impl core::fmt::Display for HorizonProfileError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "Unable to read horizon profile: {error}"),
+            Self::Parse { line, message } => {
+                write!(f, "Invalid horizon profile at line {line}: {message}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for HorizonProfileError {}
+
+impl HorizonProfile {
+    pub fn new(mut bins: Vec<HorizonBin>) -> Self {
+        bins.sort_by(|a, b| a.azimuth.total_cmp(&b.azimuth));
+        Self { bins }
+    }
+
+    /// Loads a profile from a CSV file. See [`HorizonProfile::from_csv`] for the format.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, HorizonProfileError> {
+        let csv = fs::read_to_string(path).map_err(HorizonProfileError::Io)?;
+
+        Self::from_csv(&csv)
+    }
+
+    /// Parses a profile from CSV text with one `azimuth,elevation` pair (in degrees) per line.
+    ///
+    /// Empty lines, lines starting with `#` and a non-numeric header line are ignored.
+    pub fn from_csv(csv: &str) -> Result<Self, HorizonProfileError> {
+        let mut bins = Vec::default();
+
+        for (index, line) in csv.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let parse_error = |message: String| HorizonProfileError::Parse {
+                line: index + 1,
+                message,
+            };
+            let columns = line.split(',').map(str::trim).collect::<Vec<_>>();
+
+            let [azimuth, elevation] = columns.as_slice() else {
+                return Err(parse_error(format!(
+                    "expected 2 columns (azimuth,elevation), found {}",
+                    columns.len()
+                )));
+            };
+
+            let (Ok(azimuth), Ok(elevation)) = (azimuth.parse::<f32>(), elevation.parse::<f32>())
+            else {
+                if index == 0 {
+                    continue;
+                }
+
+                return Err(parse_error(format!("`{line}` is not a pair of numbers")));
+            };
+
+            if !(0.0..360.0).contains(&azimuth) {
+                return Err(parse_error(format!(
+                    "azimuth {azimuth} is outside of [0, 360)"
+                )));
+            }
+
+            if !(0.0..=90.0).contains(&elevation) {
+                return Err(parse_error(format!(
+                    "elevation {elevation} is outside of [0, 90]"
+                )));
+            }
+
+            bins.push(HorizonBin { azimuth, elevation });
+        }
+
+        Ok(Self::new(bins))
+    }
+
+    /// Elevation of the horizon (°) in the direction of `azimuth` (°).
+    pub fn elevation_at(&self, azimuth: f32) -> f32 {
+        let azimuth = azimuth.rem_euclid(360.0);
+
+        self.bins
+            .iter()
+            .rev()
+            .find(|bin| bin.azimuth <= azimuth)
+            // Azimuths before the first bin belong to the last one, which wraps around north.
+            .or(self.bins.last())
+            .map_or(0.0, |bin| bin.elevation)
+    }
+
+    /// Whether the sun at the given position is hidden behind the horizon.
+    pub fn blocks_sun(&self, sun_azimuth: f32, sun_elevation: f32) -> bool {
+        sun_elevation <= self.elevation_at(sun_azimuth)
+    }
+
+    /// Fraction of the isotropic sky dome visible from a horizontal collector (0.0-1.0).
+    pub fn sky_view_factor(&self) -> f32 {
+        if self.bins.is_empty() {
+            return 1.0;
+        }
+
+        self.bins
+            .iter()
+            .enumerate()
+            .map(|(i, bin)| {
+                let next_azimuth = self
+                    .bins
+                    .get(i + 1)
+                    .map_or(self.bins[0].azimuth + 360.0, |next| next.azimuth);
+                let width = next_azimuth - bin.azimuth;
+
+                width * bin.elevation.to_radians().cos().powi(2)
+            })
+            .sum::<f32>()
+            / 360.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(csv: &str) -> HorizonProfile {
+        HorizonProfile::from_csv(csv).unwrap()
+    }
+
+    #[test]
+    fn empty_profile_does_not_obstruct() {
+        let profile = HorizonProfile::default();
+
+        assert_eq!(profile.elevation_at(123.0), 0.0);
+        assert_eq!(profile.sky_view_factor(), 1.0);
+        assert!(!profile.blocks_sun(180.0, 10.0));
+    }
+
+    #[test]
+    fn parses_header_comments_and_unsorted_rows() {
+        let profile = profile("azimuth,elevation\n# chimney\n180, 30\n0,5\n\n");
+
+        assert_eq!(
+            profile,
+            HorizonProfile::new(vec![
+                HorizonBin {
+                    azimuth: 180.0,
+                    elevation: 30.0
+                },
+                HorizonBin {
+                    azimuth: 0.0,
+                    elevation: 5.0
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_rows() {
+        assert!(matches!(
+            HorizonProfile::from_csv("0,5\n90,abc"),
+            Err(HorizonProfileError::Parse { line: 2, .. })
+        ));
+        assert!(matches!(
+            HorizonProfile::from_csv("0,5,1"),
+            Err(HorizonProfileError::Parse { line: 1, .. })
+        ));
+        assert!(matches!(
+            HorizonProfile::from_csv("400,5"),
+            Err(HorizonProfileError::Parse { line: 1, .. })
+        ));
+        assert!(matches!(
+            HorizonProfile::from_csv("10,95"),
+            Err(HorizonProfileError::Parse { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn elevation_wraps_around_north() {
+        let profile = profile("90,10\n270,40");
+
+        assert_eq!(profile.elevation_at(45.0), 40.0);
+        assert_eq!(profile.elevation_at(90.0), 10.0);
+        assert_eq!(profile.elevation_at(269.0), 10.0);
+        assert_eq!(profile.elevation_at(300.0), 40.0);
+        assert_eq!(profile.elevation_at(-10.0), 40.0);
+    }
+
+    #[test]
+    fn sun_behind_obstruction_is_blocked() {
+        let profile = profile("0,0\n150,35\n210,0");
+
+        assert!(profile.blocks_sun(180.0, 30.0));
+        assert!(!profile.blocks_sun(180.0, 40.0));
+        assert!(!profile.blocks_sun(90.0, 30.0));
+    }
+
+    #[test]
+    fn sky_view_factor_is_weighted_by_bin_width() {
+        let profile = profile("0,0\n180,90");
+
+        assert!((profile.sky_view_factor() - 0.5).abs() < 1e-6);
+    }
+}
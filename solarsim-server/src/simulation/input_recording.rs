@@ -0,0 +1,362 @@
+use std::{
+    fs::File,
+    io::{self, LineWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{SimulationClock, SimulationConfig, SimulationSnapshot};
+
+/// Where a recorded change came from.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ChangeSource {
+    /// A slider in the window.
+    Ui,
+    /// The `simulation.update_field` BRP method.
+    UpdateField,
+    /// The `simulation.update_fields` BRP method.
+    UpdateFields,
+    /// A scenario loaded through the window or the `simulation.load_scenario` BRP method.
+    Scenario,
+    /// The generic `world.insert_resources` and `world.mutate_resources` BRP methods.
+    WorldResources,
+}
+
+/// A change to the value of a `SimulationConfig` field.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RecordedChange {
+    /// Number of steps run before the change was made
+    pub step: u64,
+    pub source: ChangeSource,
+    pub field: String,
+    pub old_value: f32,
+    pub new_value: f32,
+}
+
+/// A replacement of the whole `SimulationConfig`, including the ranges of its fields.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RecordedConfig {
+    /// Number of steps run before the config was replaced
+    pub step: u64,
+    pub source: ChangeSource,
+    pub config: SimulationConfig,
+}
+
+/// A line of a recording after the initial state.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum RecordedInput {
+    Field(RecordedChange),
+    Config(Box<RecordedConfig>),
+}
+
+impl RecordedInput {
+    pub fn step(&self) -> u64 {
+        match self {
+            Self::Field(change) => change.step,
+            Self::Config(config) => config.step,
+        }
+    }
+}
+
+/// Writes every change to the simulation inputs to a file, one JSON object per line.
+///
+/// The first line is a [`SimulationSnapshot`] of the initial state, every other line a
+/// [`RecordedInput`].
+#[derive(Resource)]
+pub struct InputRecorder {
+    path: PathBuf,
+    writer: LineWriter<File>,
+}
+
+/// A recording loaded from a file, see [`InputRecorder`].
+pub struct InputRecording {
+    pub initial_state: SimulationSnapshot,
+    pub changes: Vec<RecordedInput>,
+}
+
+#[derive(Debug)]
+pub enum RecordingError {
+    Io(io::Error),
+    /// A line could not be parsed, the line number is 1-based.
+    Parse {
+        line: usize,
+        error: serde_json::Error,
+    },
+    Empty,
+}
+
+impl core::fmt::Display for RecordingError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "Unable to read recording: {error}"),
+            Self::Parse { line, error } => write!(f, "Invalid recording at line {line}: {error}"),
+            Self::Empty => write!(f, "Invalid recording: the file is empty"),
+        }
+    }
+}
+
+impl core::error::Error for RecordingError {}
+
+impl InputRecorder {
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer: LineWriter::new(File::create(path)?),
+        })
+    }
+
+    pub fn record(&mut self, change: RecordedChange) {
+        self.write_line(&change);
+    }
+
+    /// Records a replacement of the whole config, for changes that may also change the ranges of
+    /// the fields.
+    pub fn record_config(&mut self, step: u64, source: ChangeSource, config: &SimulationConfig) {
+        self.write_line(&RecordedConfig {
+            step,
+            source,
+            config: config.clone(),
+        });
+    }
+
+    fn write_line(&mut self, value: &impl Serialize) {
+        let result = serde_json::to_writer(&mut self.writer, value)
+            .map_err(io::Error::other)
+            .and_then(|()| self.writer.write_all(b"\n"));
+
+        if let Err(error) = result {
+            error!("Unable to record to {}: {error}", self.path.display());
+        }
+    }
+}
+
+impl InputRecording {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RecordingError> {
+        let text = std::fs::read_to_string(path).map_err(RecordingError::Io)?;
+
+        Self::parse(&text)
+    }
+
+    fn parse(text: &str) -> Result<Self, RecordingError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+        let parse_error = |index: usize| {
+            move |error| RecordingError::Parse {
+                line: index + 1,
+                error,
+            }
+        };
+        let (index, initial_state) = lines.next().ok_or(RecordingError::Empty)?;
+        let initial_state = serde_json::from_str(initial_state).map_err(parse_error(index))?;
+        let changes = lines
+            .map(|(index, line)| serde_json::from_str(line).map_err(parse_error(index)))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            initial_state,
+            changes,
+        })
+    }
+
+    /// Step of the last recorded change.
+    pub fn last_step(&self) -> u64 {
+        self.changes.last().map_or(0, RecordedInput::step)
+    }
+
+    /// Applies the changes that were made after `step` steps had run.
+    pub fn apply_changes(&self, step: u64, cfg: &mut SimulationConfig) {
+        let start = self.changes.partition_point(|change| change.step() < step);
+
+        for change in self.changes[start..]
+            .iter()
+            .take_while(|c| c.step() == step)
+        {
+            match change {
+                RecordedInput::Field(change) => {
+                    if cfg.set_field(&change.field, change.new_value).is_none() {
+                        warn!("Recorded change to unknown field {}", change.field);
+                    }
+                }
+                RecordedInput::Config(recorded) => *cfg = recorded.config.clone(),
+            }
+        }
+    }
+}
+
+/// Runs `change` and, when recording, records the `SimulationConfig` if `change` modified it.
+pub fn record_config_changes<T>(
+    world: &mut World,
+    source: ChangeSource,
+    change: impl FnOnce(&mut World) -> T,
+) -> T {
+    let old = world
+        .contains_resource::<InputRecorder>()
+        .then(|| world.get_resource::<SimulationConfig>().cloned())
+        .flatten();
+    let result = change(world);
+
+    if let Some(old) = old {
+        let step = world
+            .get_resource::<SimulationClock>()
+            .map_or(0, |clock| clock.steps);
+
+        world.resource_scope(|world, mut recorder: Mut<InputRecorder>| {
+            // Compare the serialized configs, so that changes to the ranges of the fields are
+            // recorded too.
+            if let Some(new) = world.get_resource::<SimulationConfig>()
+                && serde_json::to_value(&old).ok() != serde_json::to_value(new).ok()
+            {
+                recorder.record_config(step, source, new);
+            }
+        });
+    }
+
+    result
+}
+
+/// Writes the initial state of the simulation as the first line of the recording.
+pub(super) fn write_recording_header(world: &mut World) {
+    let snapshot = SimulationSnapshot::capture(world);
+
+    world.resource_mut::<InputRecorder>().write_line(&snapshot);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        FIXED_TIMESTEP_SECS,
+        simulation::{CloudCover, SimulationRunner, SimulationStats, SolarIrradianceDriver},
+    };
+
+    use super::*;
+
+    #[test]
+    fn replay_is_bit_identical() {
+        let mut runner = SimulationRunner::new(FIXED_TIMESTEP_SECS);
+
+        runner
+            .app_mut()
+            .insert_resource(SolarIrradianceDriver::CloudCover(CloudCover::new(3)));
+
+        let initial_state = SimulationSnapshot::capture(runner.world());
+        let mut lines = vec![serde_json::to_string(&initial_state).unwrap()];
+
+        for step in 0..2000 {
+            if step % 300 == 7 {
+                let new_value = 0.05 + (step % 7) as f32 * 0.1;
+                let world = runner.app_mut().world_mut();
+                let mut cfg = world.resource_mut::<SimulationConfig>();
+                let old_value = cfg.set_field("load_mass_flow_rate", new_value).unwrap();
+
+                lines.push(
+                    serde_json::to_string(&RecordedChange {
+                        step,
+                        source: ChangeSource::UpdateField,
+                        field: "load_mass_flow_rate".to_string(),
+                        old_value,
+                        new_value,
+                    })
+                    .unwrap(),
+                );
+            }
+
+            runner.step();
+        }
+
+        let recording = InputRecording::parse(&lines.join("\n")).unwrap();
+        let mut replay = recording.initial_state.runner();
+
+        for step in 0..2000 {
+            let world = replay.app_mut().world_mut();
+
+            recording.apply_changes(step, &mut world.resource_mut::<SimulationConfig>());
+            replay.step();
+        }
+
+        let tank_temp = |runner: &SimulationRunner| {
+            runner
+                .world()
+                .resource::<SimulationConfig>()
+                .tank_average_temp
+                .to_bits()
+        };
+        let solar_gain = |runner: &SimulationRunner| {
+            runner
+                .world()
+                .resource::<SimulationStats>()
+                .solar_gain
+                .to_bits()
+        };
+
+        assert_eq!(recording.changes.len(), 7);
+        assert_eq!(tank_temp(&replay), tank_temp(&runner));
+        assert_eq!(solar_gain(&replay), solar_gain(&runner));
+    }
+
+    #[test]
+    fn config_replacements_are_recorded_whole() {
+        // Unique to the test and process, so that concurrent test runs don't share the file.
+        let path = std::env::temp_dir().join(format!(
+            "solarsim-{}-config-replacements-are-recorded-whole.jsonl",
+            std::process::id()
+        ));
+        let mut world = World::new();
+
+        world.insert_resource(InputRecorder::create(&path).unwrap());
+        world.init_resource::<SimulationClock>();
+        world.insert_resource(SimulationConfig::default());
+
+        let mut new = SimulationConfig::default();
+
+        new.set_field("panel_area", 4.0);
+        new.panel_area.set_max(40.0);
+        record_config_changes(&mut world, ChangeSource::Scenario, |world| {
+            world.insert_resource(new);
+        });
+        // Unchanged configs are not recorded.
+        record_config_changes(&mut world, ChangeSource::WorldResources, |_| {});
+        drop(world);
+
+        // The header is only written on startup, every line is a change.
+        let text = std::fs::read_to_string(&path).unwrap();
+        let changes = text
+            .lines()
+            .map(|line| serde_json::from_str::<RecordedInput>(line).unwrap())
+            .collect::<Vec<_>>();
+
+        std::fs::remove_file(path).unwrap();
+
+        let recording = InputRecording {
+            initial_state: SimulationSnapshot::capture(SimulationRunner::new(1.0).world()),
+            changes,
+        };
+        let [RecordedInput::Config(recorded)] = recording.changes.as_slice() else {
+            panic!("expected a single config replacement");
+        };
+        let mut cfg = SimulationConfig::default();
+
+        recording.apply_changes(0, &mut cfg);
+
+        assert_eq!(recorded.source, ChangeSource::Scenario);
+        assert_eq!(*cfg.panel_area, 4.0);
+        assert_eq!(cfg.panel_area.max(), 40.0);
+    }
+
+    #[test]
+    fn invalid_lines_are_reported() {
+        let error = InputRecording::parse("{}\n").err().unwrap();
+
+        assert!(matches!(error, RecordingError::Parse { line: 1, .. }));
+        assert!(matches!(
+            InputRecording::parse("").err().unwrap(),
+            RecordingError::Empty
+        ));
+    }
+}
@@ -2,11 +2,47 @@ use core::f32::consts::PI;
 
 use bevy::prelude::*;
 
+mod clear_sky;
+mod cloud_cover;
+mod diurnal_temperature;
+mod drivers;
+mod field_metadata;
+mod field_series;
+mod horizon_profile;
+mod input_recording;
+mod scenario;
+mod scenario_migrations;
+mod simulation_clock;
 mod simulation_config;
 mod simulation_field;
-
+mod simulation_history;
+mod simulation_run_state;
+mod simulation_runner;
+mod simulation_snapshot;
+mod simulation_speed;
+mod simulation_stats;
+mod solar_position;
+
+pub use clear_sky::*;
+pub use cloud_cover::*;
+pub use diurnal_temperature::*;
+pub use drivers::*;
+pub use field_metadata::*;
+pub use field_series::*;
+pub use horizon_profile::*;
+pub use input_recording::*;
+pub use scenario::*;
+pub use scenario_migrations::*;
+pub use simulation_clock::*;
 pub use simulation_config::*;
 pub use simulation_field::*;
+pub use simulation_history::*;
+pub use simulation_run_state::*;
+pub use simulation_runner::*;
+pub use simulation_snapshot::*;
+pub use simulation_speed::*;
+pub use simulation_stats::*;
+pub use solar_position::*;
 
 /// Constant approximation of water heat capacity (J/kg·K)
 const WATER_HEAT_CAPACITY: f32 = 4181.0;
@@ -15,16 +51,65 @@ const WATER_DENSITY: f32 = 1000.0;
 
 pub fn plugin(app: &mut App) {
     app.register_type::<SimulationConfig>()
-        .init_resource::<SimulationConfig>();
+        .register_type::<SimulationClock>()
+        .register_type::<SimulationRunState>()
+        .register_type::<SimulationSpeed>()
+        .register_type::<SimulationStats>()
+        .register_type::<HorizonProfile>()
+        .register_type::<SolarIrradianceDriver>()
+        .register_type::<AmbientTempDriver>()
+        .register_type::<ActiveScenario>()
+        .init_resource::<SimulationConfig>()
+        .init_resource::<SimulationClock>()
+        .init_resource::<SimulationRunState>()
+        .init_resource::<SimulationSpeed>()
+        .init_resource::<SimulationStats>()
+        .init_resource::<HorizonProfile>()
+        .init_resource::<SolarIrradianceDriver>()
+        .init_resource::<AmbientTempDriver>()
+        .init_resource::<ActiveScenario>()
+        .init_resource::<SimulationHistory>()
+        .init_resource::<FieldSeries>();
+
+    app.add_systems(
+        Startup,
+        write_recording_header.run_if(resource_exists::<InputRecorder>),
+    );
+
+    app.add_systems(
+        Update,
+        (track_simulation_speed, apply_simulation_speed).chain(),
+    );
 
     app.add_systems(
         FixedUpdate,
-        (update_tank_geometry.before(run_simulation), run_simulation)
-            .run_if(resource_exists::<SimulationConfig>),
+        (
+            record_history,
+            record_series,
+            advance_clock,
+            (
+                drive_solar_irradiance,
+                drive_ambient_temp,
+                update_tank_geometry,
+            ),
+            run_simulation,
+            finish_step,
+        )
+            .chain()
+            .run_if(resource_exists::<SimulationConfig>.and(simulation_running)),
     );
 }
 
-fn run_simulation(time: Res<Time<Fixed>>, mut cfg: ResMut<SimulationConfig>) {
+fn advance_clock(time: Res<Time<Fixed>>, mut clock: ResMut<SimulationClock>) {
+    clock.advance(time.delta_secs_f64());
+}
+
+fn run_simulation(
+    time: Res<Time<Fixed>>,
+    horizon: Res<HorizonProfile>,
+    mut cfg: ResMut<SimulationConfig>,
+    mut stats: ResMut<SimulationStats>,
+) {
     let dt = time.delta_secs();
     let load_mdot = *cfg.load_mass_flow_rate;
     let tank_mass = *cfg.tank_water_mass;
@@ -32,8 +117,22 @@ fn run_simulation(time: Res<Time<Fixed>>, mut cfg: ResMut<SimulationConfig>) {
     let ambient_temp = *cfg.ambient_temp;
 
     let cp_water = WATER_HEAT_CAPACITY;
+    let diffuse_irradiance = *cfg.solar_irradiance * *cfg.diffuse_fraction;
+    let beam_irradiance = *cfg.solar_irradiance - diffuse_irradiance;
+    // Beam irradiance only reaches the panel when the sun is above the horizon profile.
+    let blocked_beam_irradiance = if horizon.blocks_sun(*cfg.sun_azimuth, *cfg.sun_elevation) {
+        beam_irradiance
+    } else {
+        0.0
+    };
+    // Diffuse irradiance is reduced by the portion of the sky hidden by obstructions.
+    let blocked_diffuse_irradiance = diffuse_irradiance * (1.0 - horizon.sky_view_factor());
+    let panel_output_ratio = *cfg.panel_area * *cfg.panel_efficiency;
+    // Solar heat input lost to shading.
+    let q_shading_loss =
+        (blocked_beam_irradiance + blocked_diffuse_irradiance) * panel_output_ratio;
     // Solar heat input to panel.
-    let q_solar = *cfg.solar_irradiance * *cfg.panel_area * *cfg.panel_efficiency;
+    let q_solar = *cfg.solar_irradiance * panel_output_ratio - q_shading_loss;
     // Panel heat loss.
     let q_panel_loss =
         *cfg.panel_heat_loss_coefficient * *cfg.panel_loss_area * (tank_temp - ambient_temp);
@@ -61,6 +160,26 @@ fn run_simulation(time: Res<Time<Fixed>>, mut cfg: ResMut<SimulationConfig>) {
         new_tank_temp -= delta_temp_load;
     }
 
+    // Power needed to bring the drawn water up to the delivery temperature.
+    let q_load_demand = load_mdot * cp_water * (*cfg.load_delivery_temp - *cfg.load_temp).max(0.0);
+    // Power the auxiliary heater adds when the tank is colder than the delivery temperature.
+    let q_aux = load_mdot * cp_water * (*cfg.load_delivery_temp - tank_temp).max(0.0);
+    let q_aux = q_aux.min(q_load_demand);
+
+    *cfg.shading_loss = q_shading_loss;
+
+    let to_kwh = |power: f32| f64::from(power * dt) / JOULES_PER_KWH;
+
+    stats.aux_energy += to_kwh(q_aux);
+    stats.heat_loss += to_kwh(q_panel_loss + q_pipe_loss + q_tank_loss);
+    stats.load_demand += to_kwh(q_load_demand);
+    stats.shading_loss += to_kwh(q_shading_loss);
+    stats.solar_gain += to_kwh(q_solar);
+
+    if new_tank_temp > OVERHEATING_TEMP {
+        stats.overheating_hours += f64::from(dt) / 3600.0;
+    }
+
     // Update tank average temperature.
     *cfg.tank_average_temp = new_tank_temp;
     // Update water temperature entering the panel.
@@ -68,12 +187,20 @@ fn run_simulation(time: Res<Time<Fixed>>, mut cfg: ResMut<SimulationConfig>) {
     *cfg.water_temp_in = new_tank_temp;
 }
 
-fn update_tank_geometry(mut cfg: ResMut<SimulationConfig>) {
-    let k = *cfg.tank_height_diameter_ratio;
-    let radius = (*cfg.tank_surface_area / (2.0 * PI * (1.0 + 2.0 * k))).sqrt();
+impl SimulationConfig {
+    /// Mass of water in a full tank, given its surface area and shape, measured in kg.
+    fn tank_capacity(&self) -> f32 {
+        let k = *self.tank_height_diameter_ratio;
+        let radius = (*self.tank_surface_area / (2.0 * PI * (1.0 + 2.0 * k))).sqrt();
 
-    let volume_if_full = 2.0 * PI * k * radius.powi(3);
-    let mass_if_full = volume_if_full * WATER_DENSITY;
+        let volume_if_full = 2.0 * PI * k * radius.powi(3);
+
+        volume_if_full * WATER_DENSITY
+    }
+}
+
+fn update_tank_geometry(mut cfg: ResMut<SimulationConfig>) {
+    let mass_if_full = cfg.tank_capacity();
 
     cfg.tank_water_mass.set_max(mass_if_full);
 
@@ -86,6 +213,8 @@ fn update_tank_geometry(mut cfg: ResMut<SimulationConfig>) {
 mod tests {
     use std::time::Duration;
 
+    use bevy::time::{TimePlugin, TimeUpdateStrategy};
+
     use crate::FIXED_TIMESTEP_SECS;
 
     use super::*;
@@ -94,6 +223,11 @@ mod tests {
         let mut app = App::new();
         app.insert_resource(Time::<Fixed>::from_seconds(FIXED_TIMESTEP_SECS));
         app.insert_resource(SimulationConfig::default());
+        app.init_resource::<SimulationClock>();
+        app.init_resource::<SimulationStats>();
+        app.init_resource::<HorizonProfile>();
+        app.init_resource::<SolarIrradianceDriver>();
+        app.init_resource::<AmbientTempDriver>();
         app
     }
 
@@ -160,6 +294,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn cold_tank_is_topped_up_by_aux_heater() {
+        let mut app = setup_app();
+
+        app.add_systems(Update, run_simulation);
+
+        let mut cfg = app.world_mut().resource_mut::<SimulationConfig>();
+
+        *cfg.load_mass_flow_rate = 0.1;
+        *cfg.load_temp = 10.0;
+        *cfg.load_delivery_temp = 45.0;
+        *cfg.tank_average_temp = 30.0;
+
+        app.world_mut()
+            .resource_mut::<Time<Fixed>>()
+            .advance_by(Duration::from_secs_f64(3600.0));
+        app.update();
+
+        let stats = app.world().resource::<SimulationStats>();
+        let expected_demand = 0.1 * f64::from(WATER_HEAT_CAPACITY) * 35.0 / 1000.0;
+        let expected_aux = 0.1 * f64::from(WATER_HEAT_CAPACITY) * 15.0 / 1000.0;
+
+        assert!((stats.load_demand - expected_demand).abs() < 1e-3);
+        assert!((stats.aux_energy - expected_aux).abs() < 1e-3);
+        assert!((stats.solar_fraction() - 20.0 / 35.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn time_above_overheating_temp_is_counted() {
+        let mut app = setup_app();
+
+        app.add_systems(Update, run_simulation);
+
+        let mut cfg = app.world_mut().resource_mut::<SimulationConfig>();
+
+        *cfg.load_mass_flow_rate = 0.0;
+        *cfg.tank_average_temp = OVERHEATING_TEMP + 10.0;
+
+        for _ in 0..2 {
+            app.world_mut()
+                .resource_mut::<Time<Fixed>>()
+                .advance_by(Duration::from_secs_f64(1800.0));
+            app.update();
+        }
+
+        let stats = app.world().resource::<SimulationStats>();
+
+        assert!((stats.overheating_hours - 1.0).abs() < 1e-6);
+    }
+
     #[test]
     fn zero_tank_mass_results_in_no_change() {
         let mut app = setup_app();
@@ -202,6 +386,298 @@ mod tests {
         );
     }
 
+    #[test]
+    fn horizon_blocks_beam_irradiance() {
+        let mut app = setup_app();
+
+        app.add_systems(Update, run_simulation);
+        app.insert_resource(HorizonProfile::from_csv("0,0\n150,60\n210,0").unwrap());
+
+        let mut cfg = app.world_mut().resource_mut::<SimulationConfig>();
+
+        *cfg.solar_irradiance = 1000.0;
+        *cfg.diffuse_fraction = 0.25;
+        *cfg.sun_azimuth = 180.0;
+        *cfg.sun_elevation = 30.0;
+
+        let panel_output_ratio = *cfg.panel_area * *cfg.panel_efficiency;
+
+        app.world_mut()
+            .resource_mut::<Time<Fixed>>()
+            .advance_by(Duration::from_secs_f64(FIXED_TIMESTEP_SECS));
+        app.update();
+
+        let sky_view_factor = app.world().resource::<HorizonProfile>().sky_view_factor();
+        let expected_loss = (1000.0 - 250.0 * sky_view_factor) * panel_output_ratio;
+        let cfg = app.world().resource::<SimulationConfig>();
+
+        assert!((*cfg.shading_loss - expected_loss).abs() < 1e-3);
+        assert!(app.world().resource::<SimulationStats>().shading_loss > 0.0);
+    }
+
+    #[test]
+    fn unobstructed_sky_has_no_shading_loss() {
+        let mut app = setup_app();
+
+        app.add_systems(Update, run_simulation);
+        app.world_mut()
+            .resource_mut::<Time<Fixed>>()
+            .advance_by(Duration::from_secs_f64(FIXED_TIMESTEP_SECS));
+        app.update();
+
+        assert_eq!(
+            *app.world().resource::<SimulationConfig>().shading_loss,
+            0.0
+        );
+        assert_eq!(app.world().resource::<SimulationStats>().shading_loss, 0.0);
+    }
+
+    #[test]
+    fn cloud_cover_drives_solar_irradiance() {
+        let run = |seed| {
+            let mut app = setup_app();
+
+            app.add_systems(Update, (advance_clock, drive_solar_irradiance).chain());
+            app.insert_resource(SolarIrradianceDriver::CloudCover(CloudCover::new(seed)));
+
+            (0..300)
+                .map(|_| {
+                    app.world_mut()
+                        .resource_mut::<Time<Fixed>>()
+                        .advance_by(Duration::from_secs_f64(30.0));
+                    app.update();
+
+                    *app.world().resource::<SimulationConfig>().solar_irradiance
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let irradiance = run(7);
+
+        assert_eq!(irradiance, run(7));
+        assert!(irradiance.windows(2).any(|pair| pair[0] != pair[1]));
+    }
+
+    #[test]
+    fn clear_sky_drives_irradiance_and_sun_position() {
+        let mut app = setup_app();
+
+        app.add_systems(Update, (advance_clock, drive_solar_irradiance).chain());
+        app.insert_resource(SolarIrradianceDriver::ClearSky(ClearSkyModel::Ashrae));
+
+        let irradiance_at = |app: &mut App, hours: f64| {
+            app.world_mut()
+                .resource_mut::<Time<Fixed>>()
+                .advance_by(Duration::from_secs_f64(hours * 3600.0));
+            app.update();
+
+            let cfg = app.world().resource::<SimulationConfig>();
+
+            (*cfg.solar_irradiance, *cfg.sun_azimuth)
+        };
+
+        // The clock starts at 06:00, so these are 09:00, 12:00 and 00:00.
+        let (morning, morning_azimuth) = irradiance_at(&mut app, 3.0);
+        let (noon, noon_azimuth) = irradiance_at(&mut app, 3.0);
+        let (night, _) = irradiance_at(&mut app, 12.0);
+
+        assert!(noon > morning);
+        assert!(morning_azimuth < noon_azimuth);
+        assert_eq!(night, 0.0);
+    }
+
+    #[test]
+    fn diurnal_temperature_drives_ambient_temp() {
+        let mut app = setup_app();
+
+        app.add_systems(Update, (advance_clock, drive_ambient_temp).chain());
+        app.insert_resource(AmbientTempDriver::Diurnal(DiurnalTemperature {
+            min: 10.0,
+            max: 30.0,
+            peak_hour: 15.0,
+        }));
+
+        // The clock starts at 06:00, so this is the time of peak.
+        app.world_mut()
+            .resource_mut::<Time<Fixed>>()
+            .advance_by(Duration::from_secs_f64(9.0 * 3600.0));
+        app.update();
+
+        let ambient_temp = *app.world().resource::<SimulationConfig>().ambient_temp;
+
+        assert!((ambient_temp - 30.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn clock_advances_with_steps() {
+        let mut app = setup_app();
+
+        app.add_systems(Update, advance_clock);
+
+        for _ in 0..4 {
+            app.world_mut()
+                .resource_mut::<Time<Fixed>>()
+                .advance_by(Duration::from_secs_f64(FIXED_TIMESTEP_SECS));
+            app.update();
+        }
+
+        let clock = app.world().resource::<SimulationClock>();
+
+        assert_eq!(clock.steps, 4);
+        assert_eq!(clock.elapsed_secs, 4.0 * FIXED_TIMESTEP_SECS);
+    }
+
+    #[test]
+    fn speed_is_applied_to_virtual_time() {
+        let mut app = setup_app();
+
+        app.init_resource::<Time<Virtual>>();
+        app.insert_resource(SimulationSpeed {
+            target: SpeedTarget::Multiplier(3600.0),
+            ..default()
+        });
+        app.add_systems(Update, apply_simulation_speed);
+        app.update();
+
+        let virtual_time = app.world().resource::<Time<Virtual>>();
+
+        assert_eq!(virtual_time.relative_speed_f64(), 3600.0);
+        assert_eq!(
+            virtual_time.max_delta(),
+            (Duration::from_secs_f64(FIXED_TIMESTEP_SECS) * MAX_STEPS_PER_FRAME).div_f64(3600.0)
+        );
+        assert_eq!(
+            app.world().resource::<Time<Fixed>>().timestep(),
+            Duration::from_secs_f64(FIXED_TIMESTEP_SECS)
+        );
+    }
+
+    #[test]
+    fn extreme_speeds_are_applied_without_panicking() {
+        let mut app = setup_app();
+
+        app.init_resource::<Time<Virtual>>();
+        app.add_systems(Update, apply_simulation_speed);
+
+        for multiplier in [1e-20, 1e300, f64::INFINITY, f64::NAN, -1.0] {
+            app.insert_resource(SimulationSpeed {
+                target: SpeedTarget::Multiplier(multiplier),
+                ..default()
+            });
+            app.update();
+        }
+
+        let virtual_time = app.world().resource::<Time<Virtual>>();
+
+        assert_eq!(virtual_time.relative_speed_f64(), 0.0);
+        assert!(virtual_time.max_delta() > Duration::ZERO);
+    }
+
+    #[test]
+    fn steps_per_frame_are_capped() {
+        #[derive(Resource, Default)]
+        struct Steps(u32);
+
+        let mut app = App::new();
+
+        app.add_plugins(TimePlugin);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            16,
+        )));
+        app.insert_resource(Time::<Fixed>::from_seconds(FIXED_TIMESTEP_SECS));
+        app.insert_resource(SimulationSpeed {
+            target: SpeedTarget::Max,
+            ..default()
+        });
+        app.init_resource::<Steps>();
+        app.add_systems(FixedUpdate, |mut steps: ResMut<Steps>| steps.0 += 1);
+        app.add_systems(
+            Update,
+            (track_simulation_speed, apply_simulation_speed).chain(),
+        );
+
+        let run_frame = |app: &mut App| {
+            app.world_mut().resource_mut::<Steps>().0 = 0;
+            app.update();
+
+            let speed = app.world().resource::<SimulationSpeed>();
+
+            (app.world().resource::<Steps>().0, speed.lagging)
+        };
+
+        for _ in 0..3 {
+            run_frame(&mut app);
+        }
+
+        assert_eq!(run_frame(&mut app), (MAX_STEPS_PER_FRAME, false));
+
+        // Twice the steps a frame can run.
+        let multiplier = 2.0 * f64::from(MAX_STEPS_PER_FRAME) * FIXED_TIMESTEP_SECS / 0.016;
+
+        app.world_mut().resource_mut::<SimulationSpeed>().target =
+            SpeedTarget::Multiplier(multiplier);
+        run_frame(&mut app);
+
+        let (steps, lagging) = run_frame(&mut app);
+        let achieved = app.world().resource::<SimulationSpeed>().achieved;
+
+        assert!(steps <= MAX_STEPS_PER_FRAME);
+        assert!(lagging);
+        assert!((achieved - multiplier / 2.0).abs() < multiplier * 0.01);
+
+        app.world_mut().resource_mut::<SimulationSpeed>().target = SpeedTarget::Multiplier(60.0);
+        run_frame(&mut app);
+
+        assert!(!run_frame(&mut app).1);
+    }
+
+    #[test]
+    fn paused_simulation_only_runs_requested_steps() {
+        let mut app = setup_app();
+
+        app.init_resource::<SimulationRunState>();
+        app.add_systems(
+            Update,
+            (advance_clock, finish_step)
+                .chain()
+                .run_if(simulation_running),
+        );
+
+        let run_frames = |app: &mut App, frames| {
+            for _ in 0..frames {
+                app.world_mut()
+                    .resource_mut::<Time<Fixed>>()
+                    .advance_by(Duration::from_secs_f64(FIXED_TIMESTEP_SECS));
+                app.update();
+            }
+
+            app.world().resource::<SimulationClock>().steps
+        };
+
+        assert_eq!(run_frames(&mut app, 2), 2);
+
+        app.world_mut()
+            .resource_mut::<SimulationRunState>()
+            .toggle_pause();
+
+        assert_eq!(run_frames(&mut app, 2), 2);
+
+        app.world_mut().resource_mut::<SimulationRunState>().step(1);
+
+        assert_eq!(run_frames(&mut app, 3), 3);
+        assert!(app.world().resource::<SimulationRunState>().is_paused());
+
+        app.world_mut().resource_mut::<SimulationRunState>().step(2);
+
+        assert_eq!(run_frames(&mut app, 3), 5);
+
+        app.world_mut()
+            .resource_mut::<SimulationRunState>()
+            .toggle_pause();
+
+        assert_eq!(run_frames(&mut app, 2), 7);
+    }
+
     #[test]
     fn surface_area_grows_mass_unchanged() {
         let mut app = setup_app();
@@ -0,0 +1,262 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{MIGRATIONS, SCENARIO_VERSION, SimulationConfig, SimulationField, migrate};
+
+/// Directory named scenarios are saved to and loaded from.
+pub const SCENARIOS_DIR: &str = "scenarios";
+
+/// A named set of simulation parameters that can be saved to and loaded from a JSON file.
+///
+/// Files from older versions are upgraded on load, see [`MIGRATIONS`].
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Scenario {
+    /// Version of the file format, see [`SCENARIO_VERSION`]
+    pub version: u64,
+    pub name: String,
+    /// Value, range and kind of every simulation field
+    pub config: SimulationConfig,
+}
+
+/// Name of the scenario the simulation was last loaded from or saved to.
+#[derive(Resource, Reflect, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct ActiveScenario {
+    pub name: String,
+}
+
+impl Default for ActiveScenario {
+    fn default() -> Self {
+        Self {
+            name: "default".to_string(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ScenarioError {
+    Io {
+        path: PathBuf,
+        error: io::Error,
+    },
+    /// The file is not valid JSON or does not match the scenario format.
+    Parse(serde_json::Error),
+    /// The file does not have the structure of a scenario.
+    Invalid(String),
+    /// The file was written by a newer version of the server.
+    UnsupportedVersion(u64),
+    /// Names may only contain letters, digits, `-` and `_`.
+    InvalidName(String),
+    InvalidField {
+        field: String,
+        message: String,
+    },
+}
+
+impl core::fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Io { path, error } => write!(f, "{}: {error}", path.display()),
+            Self::Parse(error) => write!(f, "Invalid scenario: {error}"),
+            Self::Invalid(message) => write!(f, "Invalid scenario: {message}"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "Scenario version {version} is newer than the supported version {SCENARIO_VERSION}"
+            ),
+            Self::InvalidName(name) => write!(
+                f,
+                "Invalid scenario name {name:?}, use only letters, digits, '-' and '_'"
+            ),
+            Self::InvalidField { field, message } => {
+                write!(f, "Invalid scenario field {field}: {message}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for ScenarioError {}
+
+impl Scenario {
+    pub fn new(name: String, config: SimulationConfig) -> Self {
+        Self {
+            version: SCENARIO_VERSION,
+            name,
+            config,
+        }
+    }
+
+    /// Path of the file a scenario with the given name is saved to, inside [`SCENARIOS_DIR`].
+    pub fn path_for(name: &str) -> Result<PathBuf, ScenarioError> {
+        let valid_name = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+        if !valid_name {
+            return Err(ScenarioError::InvalidName(name.to_string()));
+        }
+
+        Ok(Path::new(SCENARIOS_DIR).join(format!("{name}.json")))
+    }
+
+    /// Loads a scenario, upgrading it if it was saved by an older version.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ScenarioError> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path).map_err(|error| ScenarioError::Io {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        let (scenario, warnings) = Self::parse(&json)?;
+
+        for warning in warnings {
+            warn!("{}: {warning}", path.display());
+        }
+
+        Ok(scenario)
+    }
+
+    /// Parses, migrates and validates a scenario, returning it with the migration warnings.
+    fn parse(json: &str) -> Result<(Self, Vec<String>), ScenarioError> {
+        let mut value = serde_json::from_str(json).map_err(ScenarioError::Parse)?;
+        let warnings = migrate(&mut value, MIGRATIONS)?;
+        let scenario = serde_json::from_value::<Self>(value).map_err(ScenarioError::Parse)?;
+
+        scenario.validate()?;
+
+        Ok((scenario, warnings))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ScenarioError> {
+        let path = path.as_ref();
+        let io_error = |error| ScenarioError::Io {
+            path: path.to_path_buf(),
+            error,
+        };
+        let json = serde_json::to_string_pretty(self).map_err(ScenarioError::Parse)?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(io_error)?;
+        }
+
+        fs::write(path, json).map_err(io_error)
+    }
+
+    /// Loads the scenario with the given name from [`SCENARIOS_DIR`].
+    pub fn load_named(name: &str) -> Result<Self, ScenarioError> {
+        Self::load(Self::path_for(name)?)
+    }
+
+    /// Saves the scenario to [`SCENARIOS_DIR`] under its name, returning the file path.
+    pub fn save_named(&self) -> Result<PathBuf, ScenarioError> {
+        let path = Self::path_for(&self.name)?;
+
+        self.save(&path)?;
+
+        Ok(path)
+    }
+
+    fn validate(&self) -> Result<(), ScenarioError> {
+        let Ok(config) = self.config.reflect_ref().as_struct() else {
+            return Ok(());
+        };
+
+        for (index, field) in config.iter_fields().enumerate() {
+            if let Some(field) = field.try_downcast_ref::<SimulationField>()
+                && let Err(message) = field.validate()
+            {
+                return Err(ScenarioError::InvalidField {
+                    field: config.name_at(index).unwrap_or_default().to_string(),
+                    message,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenario_json() -> serde_json::Value {
+        serde_json::to_value(Scenario::new(
+            "test".to_string(),
+            SimulationConfig::default(),
+        ))
+        .unwrap()
+    }
+
+    fn parse(json: &serde_json::Value) -> Result<Scenario, ScenarioError> {
+        Scenario::parse(&json.to_string()).map(|(scenario, _)| scenario)
+    }
+
+    #[test]
+    fn scenario_round_trips_through_json() {
+        let json = scenario_json();
+        let scenario = parse(&json).unwrap();
+
+        assert_eq!(scenario.name, "test");
+        assert_eq!(serde_json::to_value(&scenario).unwrap(), json);
+    }
+
+    #[test]
+    fn value_outside_of_range_is_rejected() {
+        let mut json = scenario_json();
+
+        json["config"]["panel_area"]["value"] = 10.0.into();
+
+        let error = parse(&json).err().unwrap();
+
+        assert!(matches!(
+            error,
+            ScenarioError::InvalidField { field, .. } if field == "panel_area"
+        ));
+    }
+
+    #[test]
+    fn saved_read_only_values_outside_of_range_load() {
+        let path = std::env::temp_dir().join(format!(
+            "solarsim-{}-saved-read-only-values.json",
+            std::process::id()
+        ));
+        let mut config = SimulationConfig::default();
+
+        // The simulation heats the tank past the top of the slider range.
+        config.set_field("tank_average_temp", 85.0);
+        Scenario::new("hot".to_string(), config)
+            .save(&path)
+            .unwrap();
+
+        let loaded = Scenario::load(&path);
+
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(*loaded.unwrap().config.tank_average_temp, 85.0);
+    }
+
+    #[test]
+    fn malformed_field_is_a_parse_error() {
+        let mut json = scenario_json();
+
+        json["config"]["panel_area"]["kind"] = "Knob".into();
+
+        let error = parse(&json).err().unwrap();
+
+        assert!(matches!(error, ScenarioError::Parse(_)));
+        assert!(error.to_string().contains("Knob"));
+    }
+
+    #[test]
+    fn names_cannot_escape_the_scenarios_dir() {
+        assert!(Scenario::path_for("summer_2025").is_ok());
+        assert!(Scenario::path_for("").is_err());
+        assert!(Scenario::path_for("../secrets").is_err());
+        assert!(Scenario::path_for("a/b").is_err());
+    }
+}
@@ -0,0 +1,259 @@
+use serde_json::{Map, Value};
+
+use super::{ScenarioError, SimulationConfig};
+
+/// Version of the scenario files written by this build.
+pub const SCENARIO_VERSION: u64 = 2;
+
+/// Change to a single `SimulationConfig` field between two scenario versions.
+#[cfg_attr(
+    not(test),
+    expect(
+        dead_code,
+        reason = "no field has changed since the version header was introduced"
+    )
+)]
+pub(super) enum FieldMigration {
+    Rename {
+        from: &'static str,
+        to: &'static str,
+    },
+    Remove(&'static str),
+    /// Converts the value and range to a new unit, as `old * scale + offset`.
+    ConvertUnit {
+        field: &'static str,
+        scale: f64,
+        offset: f64,
+    },
+}
+
+/// Changes that upgrade a scenario from the previous version to `version`.
+pub(super) struct Migration {
+    pub version: u64,
+    pub fields: &'static [FieldMigration],
+}
+
+/// Every migration, in version order. Files without a version header are version 1.
+///
+/// When renaming, removing or changing the unit of a `SimulationConfig` field, bump
+/// [`SCENARIO_VERSION`] and describe the change here so stored scenarios keep loading.
+pub(super) const MIGRATIONS: &[Migration] = &[
+    // Version 2 introduced the version header.
+    Migration {
+        version: 2,
+        fields: &[],
+    },
+];
+
+/// Upgrades a scenario to [`SCENARIO_VERSION`] and fills in the fields it lacks with their
+/// defaults, returning a warning for every change made.
+pub(super) fn migrate(
+    scenario: &mut Value,
+    migrations: &[Migration],
+) -> Result<Vec<String>, ScenarioError> {
+    let Some(scenario) = scenario.as_object_mut() else {
+        return Err(ScenarioError::Invalid(
+            "expected a JSON object at the top level".to_string(),
+        ));
+    };
+    let version = match scenario.get("version") {
+        None => 1,
+        Some(version) => version.as_u64().ok_or_else(|| {
+            ScenarioError::Invalid(format!("version {version} is not a positive integer"))
+        })?,
+    };
+    let latest = migrations.last().map_or(1, |migration| migration.version);
+
+    if version > latest {
+        return Err(ScenarioError::UnsupportedVersion(version));
+    }
+
+    let Some(config) = scenario.get_mut("config").and_then(Value::as_object_mut) else {
+        return Err(ScenarioError::Invalid("missing config object".to_string()));
+    };
+    let mut warnings = Vec::default();
+
+    for migration in migrations.iter().filter(|m| m.version > version) {
+        for field in migration.fields {
+            apply(config, field);
+        }
+    }
+
+    if version < latest {
+        warnings.push(format!("upgraded from version {version} to {latest}"));
+    }
+
+    warnings.extend(fill_missing_fields(config));
+    scenario.insert("version".to_string(), latest.into());
+
+    Ok(warnings)
+}
+
+fn apply(config: &mut Map<String, Value>, migration: &FieldMigration) {
+    match migration {
+        FieldMigration::Rename { from, to } => {
+            if let Some(field) = config.remove(*from) {
+                config.insert(to.to_string(), field);
+            }
+        }
+        FieldMigration::Remove(field) => {
+            config.remove(*field);
+        }
+        FieldMigration::ConvertUnit {
+            field,
+            scale,
+            offset,
+        } => {
+            let Some(field) = config.get_mut(*field).and_then(Value::as_object_mut) else {
+                return;
+            };
+
+            for key in ["value", "min", "max"] {
+                if let Some(old) = field.get(key).and_then(Value::as_f64) {
+                    field.insert(key.to_string(), (old * scale + offset).into());
+                }
+            }
+        }
+    }
+}
+
+/// Adds the fields, or parts of a field, missing from `config` with their default values.
+fn fill_missing_fields(config: &mut Map<String, Value>) -> Vec<String> {
+    let Ok(Value::Object(defaults)) = serde_json::to_value(SimulationConfig::default()) else {
+        return Vec::default();
+    };
+    let mut warnings = Vec::default();
+
+    for (name, default_field) in defaults {
+        match config.get_mut(&name) {
+            None => {
+                warnings.push(format!("field {name} is missing, using its default"));
+                config.insert(name, default_field);
+            }
+            Some(Value::Object(field)) => {
+                let Value::Object(default_field) = default_field else {
+                    continue;
+                };
+
+                for (key, default_value) in default_field {
+                    if !field.contains_key(&key) {
+                        warnings.push(format!("{name}.{key} is missing, using its default"));
+                        field.insert(key, default_value);
+                    }
+                }
+            }
+            // Left for deserialization to report.
+            Some(_) => {}
+        }
+    }
+
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn scenario_json(version: Option<u64>) -> Value {
+        let mut scenario = json!({
+            "name": "test",
+            "config": serde_json::to_value(SimulationConfig::default()).unwrap(),
+        });
+
+        if let Some(version) = version {
+            scenario["version"] = version.into();
+        }
+
+        scenario
+    }
+
+    #[test]
+    fn unversioned_files_are_upgraded() {
+        let mut scenario = scenario_json(None);
+        let warnings = migrate(&mut scenario, MIGRATIONS).unwrap();
+
+        assert_eq!(scenario["version"], SCENARIO_VERSION);
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn current_files_are_unchanged() {
+        let mut scenario = scenario_json(Some(SCENARIO_VERSION));
+        let original = scenario.clone();
+
+        assert!(migrate(&mut scenario, MIGRATIONS).unwrap().is_empty());
+        assert_eq!(scenario, original);
+    }
+
+    #[test]
+    fn newer_files_are_rejected() {
+        let mut scenario = scenario_json(Some(SCENARIO_VERSION + 1));
+
+        assert!(matches!(
+            migrate(&mut scenario, MIGRATIONS),
+            Err(ScenarioError::UnsupportedVersion(version)) if version == SCENARIO_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn fields_are_renamed_removed_and_converted_in_order() {
+        const TEST_MIGRATIONS: &[Migration] = &[
+            Migration {
+                version: 2,
+                fields: &[
+                    FieldMigration::Rename {
+                        from: "collector_area",
+                        to: "panel_area",
+                    },
+                    FieldMigration::Remove("pump_power"),
+                ],
+            },
+            Migration {
+                version: 3,
+                fields: &[FieldMigration::ConvertUnit {
+                    field: "ambient_temp",
+                    scale: 1.0,
+                    offset: -273.15,
+                }],
+            },
+        ];
+        let mut scenario = scenario_json(Some(1));
+        let config = scenario["config"].as_object_mut().unwrap();
+        let panel_area = config.remove("panel_area").unwrap();
+
+        config.insert("collector_area".to_string(), panel_area.clone());
+        config.insert("pump_power".to_string(), json!({ "value": 1.0 }));
+        config.insert(
+            "ambient_temp".to_string(),
+            json!({ "kind": "Slider", "min": 248.15, "max": 318.15, "value": 298.15 }),
+        );
+
+        let warnings = migrate(&mut scenario, TEST_MIGRATIONS).unwrap();
+        let config = &scenario["config"];
+
+        assert_eq!(warnings, ["upgraded from version 1 to 3"]);
+        assert_eq!(scenario["version"], 3);
+        assert_eq!(config["panel_area"], panel_area);
+        assert!(config.get("collector_area").is_none());
+        assert!(config.get("pump_power").is_none());
+        assert!((config["ambient_temp"]["value"].as_f64().unwrap() - 25.0).abs() < 1e-9);
+        assert!((config["ambient_temp"]["min"].as_f64().unwrap() + 25.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn missing_fields_fall_back_to_defaults() {
+        let mut scenario = scenario_json(Some(SCENARIO_VERSION));
+        let config = scenario["config"].as_object_mut().unwrap();
+
+        config.remove("panel_area");
+        config["load_temp"].as_object_mut().unwrap().remove("max");
+
+        let warnings = migrate(&mut scenario, MIGRATIONS).unwrap();
+        let defaults = serde_json::to_value(SimulationConfig::default()).unwrap();
+
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(scenario["config"], defaults);
+    }
+}
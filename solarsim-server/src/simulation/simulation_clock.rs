@@ -0,0 +1,311 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const SECS_PER_DAY: f64 = 86_400.0;
+
+/// Calendar clock of the simulation.
+///
+/// Holds the local date and time the simulation started at and the simulated time elapsed since
+/// then, which is advanced on every simulation step.
+#[derive(Resource, Reflect, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[reflect(Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimulationClock {
+    /// Local date and time the simulation started at
+    pub start: CalendarDateTime,
+    /// Offset of the local time zone from UTC, measured in hours
+    pub utc_offset: f32,
+    /// Simulated time elapsed since `start`, measured in seconds
+    pub elapsed_secs: f64,
+    /// Number of simulation steps run since `start`
+    pub steps: u64,
+}
+
+/// A date and time of the proleptic Gregorian calendar.
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[reflect(Serialize, Deserialize)]
+pub struct CalendarDateTime {
+    pub year: i32,
+    /// 1-12
+    pub month: u32,
+    /// 1-31
+    pub day: u32,
+    /// 0-23
+    pub hour: u32,
+    /// 0-59
+    pub minute: u32,
+    /// 0-59
+    pub second: u32,
+}
+
+impl SimulationClock {
+    /// Advances the clock by one step of `dt` seconds.
+    pub fn advance(&mut self, dt: f64) {
+        self.elapsed_secs += dt;
+        self.steps += 1;
+    }
+
+    /// Current local date and time.
+    pub fn now(&self) -> CalendarDateTime {
+        CalendarDateTime::from_secs(self.start.to_secs() + self.elapsed_secs)
+    }
+
+    /// Current local time of the day, measured in hours (0.0-24.0).
+    pub fn hour_of_day(&self) -> f32 {
+        ((self.start.to_secs() + self.elapsed_secs).rem_euclid(SECS_PER_DAY) / 3600.0) as f32
+    }
+
+    /// Current day of the year (starting at 1.0) and local solar time in hours, at the given
+    /// longitude (degrees, positive east).
+    pub fn solar_time(&self, longitude: f32) -> (f32, f32) {
+        let now = self.now();
+        let day_of_year = now.day_of_year() as f32;
+        // Correction between the standard meridian of the time zone and the site, in hours.
+        let longitude_correction = (longitude - 15.0 * self.utc_offset) / 15.0;
+        let solar_hour =
+            self.hour_of_day() + longitude_correction + equation_of_time(day_of_year) / 60.0;
+
+        (day_of_year, solar_hour.rem_euclid(24.0))
+    }
+}
+
+impl Default for SimulationClock {
+    fn default() -> Self {
+        Self {
+            start: CalendarDateTime {
+                year: 2025,
+                month: 6,
+                day: 21,
+                hour: 6,
+                minute: 0,
+                second: 0,
+            },
+            utc_offset: 0.0,
+            elapsed_secs: 0.0,
+            steps: 0,
+        }
+    }
+}
+
+impl core::fmt::Display for SimulationClock {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let offset_minutes = (self.utc_offset * 60.0).round() as i32;
+        let sign = if offset_minutes < 0 { '-' } else { '+' };
+
+        write!(
+            f,
+            "{} (UTC{sign}{:02}:{:02})",
+            self.now(),
+            offset_minutes.abs() / 60,
+            offset_minutes.abs() % 60
+        )
+    }
+}
+
+impl CalendarDateTime {
+    /// Seconds since 1970-01-01 00:00:00 in the same time zone.
+    fn to_secs(self) -> f64 {
+        let days = days_from_civil(self.year, self.month.clamp(1, 12), self.day);
+
+        days as f64 * SECS_PER_DAY
+            + f64::from(self.hour) * 3600.0
+            + f64::from(self.minute) * 60.0
+            + f64::from(self.second)
+    }
+
+    fn from_secs(secs: f64) -> Self {
+        let days = (secs / SECS_PER_DAY).floor();
+        let secs_of_day = (secs - days * SECS_PER_DAY) as u32;
+        let (year, month, day) = civil_from_days(days as i64);
+
+        Self {
+            year,
+            month,
+            day,
+            hour: secs_of_day / 3600,
+            minute: secs_of_day % 3600 / 60,
+            second: secs_of_day % 60,
+        }
+    }
+
+    /// Checks that the date exists and the time is within a day.
+    pub fn validate(&self) -> Result<(), String> {
+        let valid_date = (1..=12).contains(&self.month)
+            && self.day >= 1
+            && civil_from_days(days_from_civil(self.year, self.month, self.day))
+                == (self.year, self.month, self.day);
+
+        if !valid_date {
+            return Err(format!(
+                "{:04}-{:02}-{:02} is not a valid date",
+                self.year, self.month, self.day
+            ));
+        }
+
+        if self.hour > 23 || self.minute > 59 || self.second > 59 {
+            return Err(format!(
+                "{:02}:{:02}:{:02} is not a valid time",
+                self.hour, self.minute, self.second
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Day of the year, starting at 1 for January 1st.
+    pub fn day_of_year(&self) -> u32 {
+        (days_from_civil(self.year, self.month.clamp(1, 12), self.day)
+            - days_from_civil(self.year, 1, 1)
+            + 1) as u32
+    }
+}
+
+impl core::fmt::Display for CalendarDateTime {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+/// Days since 1970-01-01 of the given date (Howard Hinnant's algorithm).
+fn days_from_civil(year: i32, month: u32, day: u32) -> i64 {
+    let year = i64::from(year) - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Date of the given days since 1970-01-01 (Howard Hinnant's algorithm).
+fn civil_from_days(days: i64) -> (i32, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year as i32, month as u32, day as u32)
+}
+
+/// Difference between solar and mean time, measured in minutes (Spencer, 1971).
+fn equation_of_time(day_of_year: f32) -> f32 {
+    let b = (360.0 * (day_of_year - 1.0) / 365.0).to_radians();
+
+    229.2
+        * (0.000075 + 0.001868 * b.cos()
+            - 0.032077 * b.sin()
+            - 0.014615 * (2.0 * b).cos()
+            - 0.04089 * (2.0 * b).sin())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock(year: i32, month: u32, day: u32, hour: u32) -> SimulationClock {
+        SimulationClock {
+            start: CalendarDateTime {
+                year,
+                month,
+                day,
+                hour,
+                minute: 0,
+                second: 0,
+            },
+            ..default()
+        }
+    }
+
+    #[test]
+    fn civil_days_round_trip() {
+        for days in [-800_000, -1, 0, 1, 11_016, 20_000, 800_000] {
+            let (year, month, day) = civil_from_days(days);
+
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+    }
+
+    #[test]
+    fn invalid_dates_and_times_are_rejected() {
+        let date = |year, month, day, hour| clock(year, month, day, hour).start.validate();
+
+        assert!(date(2024, 2, 29, 23).is_ok());
+        assert!(date(2025, 2, 29, 0).is_err());
+        assert!(date(2025, 13, 1, 0).is_err());
+        assert!(date(2025, 4, 0, 0).is_err());
+        assert!(date(2025, 4, 31, 0).is_err());
+        assert!(date(2025, 4, 30, 24).is_err());
+    }
+
+    #[test]
+    fn advances_across_midnight_and_new_year() {
+        let mut clock = clock(2024, 12, 31, 23);
+
+        clock.advance(3600.0 + 61.0);
+
+        assert_eq!(
+            clock.now(),
+            CalendarDateTime {
+                year: 2025,
+                month: 1,
+                day: 1,
+                hour: 0,
+                minute: 1,
+                second: 1,
+            }
+        );
+        assert_eq!(clock.steps, 1);
+    }
+
+    #[test]
+    fn day_of_year_handles_leap_years() {
+        assert_eq!(clock(2024, 3, 1, 0).now().day_of_year(), 61);
+        assert_eq!(clock(2025, 3, 1, 0).now().day_of_year(), 60);
+        assert_eq!(clock(2024, 12, 31, 0).now().day_of_year(), 366);
+    }
+
+    #[test]
+    fn solar_time_accounts_for_longitude_and_time_zone() {
+        let mut clock = clock(2025, 4, 15, 12);
+
+        // Close to zero equation of time on this date.
+        let (_, solar_hour) = clock.solar_time(0.0);
+        assert!((solar_hour - 12.0).abs() < 0.05);
+
+        // 15° east of the standard meridian is one hour ahead.
+        let (_, solar_hour) = clock.solar_time(15.0);
+        assert!((solar_hour - 13.0).abs() < 0.05);
+
+        clock.utc_offset = 1.0;
+        let (_, solar_hour) = clock.solar_time(15.0);
+        assert!((solar_hour - 12.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn displays_date_and_offset() {
+        let mut clock = clock(2025, 6, 21, 6);
+
+        clock.utc_offset = -3.5;
+        clock.advance(90.0);
+
+        assert_eq!(clock.to_string(), "2025-06-21 06:01:30 (UTC-03:30)");
+    }
+}
@@ -1,59 +1,165 @@
+use std::collections::BTreeMap;
+
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use super::{SimulationField, SimulationFieldKind};
+use super::{FieldChange, FieldError, FieldUpdateMode, SimulationField, SimulationFieldKind};
 
-#[derive(Resource, Reflect, Serialize, Deserialize)]
+/// Parameters and state of the simulated system, one [`SimulationField`] per quantity.
+///
+/// The label, unit and description of every field are in
+/// [`FIELD_METADATA`](super::FIELD_METADATA), the single place they are documented.
+#[derive(Resource, Reflect, Serialize, Deserialize, Clone)]
 #[reflect(Resource, Serialize, Deserialize)]
 pub struct SimulationConfig {
-    /// Ambient temperature, measured in °C
     pub(super) ambient_temp: SimulationField,
-    /// Mass flow rate of water drawn from the storage tank by the user (kg/s)
+    pub(super) diffuse_fraction: SimulationField,
+    pub(super) latitude: SimulationField,
+    pub(super) load_delivery_temp: SimulationField,
     pub(super) load_mass_flow_rate: SimulationField,
-    /// Temperature of incoming water, measured in °C
     pub(super) load_temp: SimulationField,
-    /// Solar panel area, measured in m²
+    pub(super) longitude: SimulationField,
     pub(super) panel_area: SimulationField,
-    /// Solar panel efficiency factor "η" (0.0-1.0)
     pub(super) panel_efficiency: SimulationField,
-    /// Panel heat loss coefficient, measured in W/(m²·K)
     pub(super) panel_heat_loss_coefficient: SimulationField,
-    /// Solar panel loss area, measured in m²
     pub(super) panel_loss_area: SimulationField,
-    /// Outer surface area of the pipe, measured in m²
     pub(super) pipe_outer_surface_area: SimulationField,
-    /// Overall heat transfer coefficient for pipe insulation, measured in W/(m²·K)
     pub(super) pipe_overall_heat_transfer_coefficient: SimulationField,
-    /// Volumetric pump flow rate, measured in m³/s
     pub(super) pump_flow_rate: SimulationField,
-    /// Solar irradiance (energy/area), measured in W/m²
+    pub(super) shading_loss: SimulationField,
     pub(super) solar_irradiance: SimulationField,
-    /// Average tank temperature, measured in °C
+    pub(super) sun_azimuth: SimulationField,
+    pub(super) sun_elevation: SimulationField,
     pub(super) tank_average_temp: SimulationField,
-    /// Tank heat loss coefficient, measured in W/(m²·K)
     pub(super) tank_heat_loss_coefficient: SimulationField,
-    /// Tank height to diameter ratio to define its shape.
     pub(super) tank_height_diameter_ratio: SimulationField,
-    /// Tank surface area, measured in m²
     pub(super) tank_surface_area: SimulationField,
-    /// Amount of water in the tank, measured in Kg
     pub(super) tank_water_mass: SimulationField,
-    /// Water temperature entering panel, measured in °C
     pub(super) water_temp_in: SimulationField,
 }
 
+impl SimulationConfig {
+    /// The field named `name`, or `None` if there is no such field.
+    pub fn field(&self, name: &str) -> Option<&SimulationField> {
+        self.reflect_ref()
+            .as_struct()
+            .ok()?
+            .field(name)?
+            .try_downcast_ref::<SimulationField>()
+    }
+
+    /// Sets the value of the field named `name`, returning its previous value, or `None` if there
+    /// is no such field.
+    pub fn set_field(&mut self, name: &str, value: f32) -> Option<f32> {
+        let field = self
+            .reflect_mut()
+            .as_struct()
+            .ok()?
+            .field_mut(name)?
+            .try_downcast_mut::<SimulationField>()?;
+
+        Some(core::mem::replace(&mut **field, value))
+    }
+
+    /// Sets the value of a field the way the UI and BRP do, checking its kind and range.
+    pub fn update_field(
+        &mut self,
+        name: &str,
+        value: f32,
+        mode: FieldUpdateMode,
+    ) -> Result<FieldChange, FieldError> {
+        let new_value = self
+            .field(name)
+            .ok_or(FieldError::UnknownField)?
+            .check(value, mode)?;
+        let old_value = self
+            .set_field(name, new_value)
+            .ok_or(FieldError::UnknownField)?;
+
+        Ok(FieldChange {
+            old_value,
+            new_value,
+            clamped: new_value != value,
+        })
+    }
+
+    /// Sets the value of several fields at once, checking them like [`Self::update_field`].
+    ///
+    /// The values are checked together, against the ranges that result from all of them: the
+    /// capacity of the tank depends on its surface area and shape. Either every field is set, or
+    /// none is and the error of every invalid field is returned.
+    pub fn update_fields(
+        &mut self,
+        values: &BTreeMap<String, f32>,
+        mode: FieldUpdateMode,
+    ) -> Result<BTreeMap<String, FieldChange>, BTreeMap<String, FieldError>> {
+        let mut updated = self.clone();
+        let mut errors = BTreeMap::default();
+
+        for (name, value) in values {
+            if updated.set_field(name, *value).is_none() {
+                errors.insert(name.clone(), FieldError::UnknownField);
+            }
+        }
+
+        let capacity = updated.tank_capacity();
+
+        updated.tank_water_mass.set_max(capacity);
+
+        let mut changes = BTreeMap::default();
+
+        for (name, value) in values {
+            let (Some(old_field), Some(field)) = (self.field(name), updated.field(name)) else {
+                continue;
+            };
+
+            match field.check(*value, mode) {
+                Ok(new_value) => {
+                    changes.insert(
+                        name.clone(),
+                        FieldChange {
+                            old_value: **old_field,
+                            new_value,
+                            clamped: new_value != *value,
+                        },
+                    );
+                }
+                Err(error) => {
+                    errors.insert(name.clone(), error);
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        for (name, change) in &changes {
+            updated.set_field(name, change.new_value);
+        }
+
+        *self = updated;
+
+        Ok(changes)
+    }
+}
+
 impl Default for SimulationConfig {
     fn default() -> Self {
         Self {
             ambient_temp: SimulationField::new(25.0, -25.0, 45.0, SimulationFieldKind::Slider),
+            diffuse_fraction: SimulationField::new_percentile(0.2),
+            latitude: SimulationField::new(40.0, -90.0, 90.0, SimulationFieldKind::Slider),
             panel_heat_loss_coefficient: SimulationField::new(
                 0.0,
                 4.0,
                 19.0,
                 SimulationFieldKind::Slider,
             ),
+            load_delivery_temp: SimulationField::new(45.0, 30.0, 70.0, SimulationFieldKind::Slider),
             load_mass_flow_rate: SimulationField::new(0.1, 0.0, 10.0, SimulationFieldKind::Slider),
             load_temp: SimulationField::new(20.0, 10.0, 60.0, SimulationFieldKind::Slider),
+            longitude: SimulationField::new(0.0, -180.0, 180.0, SimulationFieldKind::Slider),
             panel_area: SimulationField::new(2.0, 1.0, 3.0, SimulationFieldKind::Slider),
             panel_efficiency: SimulationField::new_percentile(0.25),
             panel_loss_area: SimulationField::new(0.1, 0.0, 3.0, SimulationFieldKind::Slider),
@@ -70,7 +176,10 @@ impl Default for SimulationConfig {
                 SimulationFieldKind::Slider,
             ),
             pump_flow_rate: SimulationField::new(0.05, 0.05, 0.5, SimulationFieldKind::Slider),
+            shading_loss: SimulationField::new(0.0, 0.0, 5000.0, SimulationFieldKind::ReadOnly),
             solar_irradiance: SimulationField::new(800.0, 0.0, 1365.4, SimulationFieldKind::Slider),
+            sun_azimuth: SimulationField::new(180.0, 0.0, 360.0, SimulationFieldKind::Slider),
+            sun_elevation: SimulationField::new(45.0, 0.0, 90.0, SimulationFieldKind::Slider),
             tank_average_temp: SimulationField::new(
                 25.0,
                 10.0,
@@ -95,3 +204,102 @@ impl Default for SimulationConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(values: &[(&str, f32)]) -> BTreeMap<String, f32> {
+        values
+            .iter()
+            .map(|(name, value)| (name.to_string(), *value))
+            .collect()
+    }
+
+    #[test]
+    fn fields_are_checked_against_the_ranges_they_result_in() {
+        let mut cfg = SimulationConfig::default();
+        let mode = FieldUpdateMode::default();
+
+        cfg.update_fields(&values(&[]), mode).unwrap();
+
+        assert!(
+            cfg.update_fields(&values(&[("tank_water_mass", 1500.0)]), mode)
+                .is_err()
+        );
+        assert!(
+            cfg.update_fields(
+                &values(&[("tank_surface_area", 10.0), ("tank_water_mass", 1500.0)]),
+                mode
+            )
+            .is_ok()
+        );
+        assert_eq!(*cfg.tank_water_mass, 1500.0);
+    }
+
+    #[test]
+    fn no_field_is_set_when_one_is_invalid() {
+        let mut cfg = SimulationConfig::default();
+        let errors = cfg
+            .update_fields(
+                &values(&[
+                    ("panel_area", 2.5),
+                    ("panel_efficiency", 1.5),
+                    ("panel_size", 1.0),
+                    ("tank_average_temp", 40.0),
+                ]),
+                FieldUpdateMode::default(),
+            )
+            .unwrap_err();
+
+        assert_eq!(
+            errors,
+            BTreeMap::from([
+                (
+                    "panel_efficiency".to_string(),
+                    FieldError::OutOfRange {
+                        value: 1.5,
+                        min: 0.0,
+                        max: 1.0
+                    }
+                ),
+                ("panel_size".to_string(), FieldError::UnknownField),
+                ("tank_average_temp".to_string(), FieldError::ReadOnly),
+            ])
+        );
+        assert_eq!(*cfg.panel_area, 2.0);
+    }
+
+    #[test]
+    fn modes_allow_clamping_and_setting_read_only_fields() {
+        let mut cfg = SimulationConfig::default();
+
+        assert_eq!(
+            cfg.update_field(
+                "panel_area",
+                5.0,
+                FieldUpdateMode {
+                    clamp: true,
+                    ..default()
+                }
+            ),
+            Ok(FieldChange {
+                old_value: 2.0,
+                new_value: 3.0,
+                clamped: true
+            })
+        );
+        assert!(
+            cfg.update_field(
+                "tank_average_temp",
+                40.0,
+                FieldUpdateMode {
+                    override_read_only: true,
+                    ..default()
+                }
+            )
+            .is_ok()
+        );
+        assert_eq!(*cfg.tank_average_temp, 40.0);
+    }
+}
@@ -4,7 +4,7 @@ use bevy::{
 };
 use serde::{Deserialize, Serialize};
 
-#[derive(Reflect, Serialize, Deserialize, PartialEq, Clone, Copy, Default)]
+#[derive(Reflect, Serialize, Deserialize, PartialEq, Clone, Copy, Default, Debug)]
 #[reflect(Serialize, Deserialize)]
 pub enum SimulationFieldKind {
     /// This field is editable via a Slider
@@ -14,7 +14,54 @@ pub enum SimulationFieldKind {
     ReadOnly,
 }
 
-#[derive(Reflect, Serialize, Deserialize, Deref, DerefMut)]
+/// How a field update made through the UI or BRP treats values it would otherwise refuse.
+#[derive(Deserialize, Clone, Copy, Default, Debug)]
+#[serde(default)]
+pub struct FieldUpdateMode {
+    /// Clamp out-of-range values to the range of the field instead of refusing them
+    pub clamp: bool,
+    /// Allow setting read-only fields, which are otherwise only set by the simulation
+    pub override_read_only: bool,
+}
+
+/// Rule a field update broke.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum FieldError {
+    UnknownField,
+    /// The field is only set by the simulation.
+    ReadOnly,
+    OutOfRange {
+        value: f32,
+        min: f32,
+        max: f32,
+    },
+}
+
+impl core::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnknownField => write!(f, "Unknown field"),
+            Self::ReadOnly => write!(f, "Field is read-only"),
+            Self::OutOfRange { value, min, max } => {
+                write!(f, "Value {value} is outside of {min}..={max}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for FieldError {}
+
+/// Outcome of a field update.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct FieldChange {
+    pub old_value: f32,
+    pub new_value: f32,
+    /// Whether the requested value was clamped to the range of the field
+    pub clamped: bool,
+}
+
+#[derive(Reflect, Serialize, Deserialize, Deref, DerefMut, Clone)]
 #[reflect(Serialize, Deserialize)]
 pub struct SimulationField {
     kind: SimulationFieldKind,
@@ -58,6 +105,50 @@ impl SimulationField {
     pub fn set_max(&mut self, max: f32) {
         self.max = max;
     }
+
+    /// Checks that `value` may be set through the UI or BRP, returning the value to set.
+    pub fn check(&self, value: f32, mode: FieldUpdateMode) -> Result<f32, FieldError> {
+        if self.kind == SimulationFieldKind::ReadOnly && !mode.override_read_only {
+            return Err(FieldError::ReadOnly);
+        }
+
+        if (self.min..=self.max).contains(&value) {
+            Ok(value)
+        } else if mode.clamp && !value.is_nan() {
+            Ok(value.clamp(self.min, self.max))
+        } else {
+            Err(FieldError::OutOfRange {
+                value,
+                min: self.min,
+                max: self.max,
+            })
+        }
+    }
+
+    /// Checks that the range is well formed and holds the value.
+    ///
+    /// The value of a read-only field is computed by the simulation, which may take it out of the
+    /// range, so it only has to be finite.
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.min.is_finite() || !self.max.is_finite() || !self.value.is_finite() {
+            return Err("value, min and max must be finite numbers".to_string());
+        }
+
+        if self.min > self.max {
+            return Err(format!("min {} is greater than max {}", self.min, self.max));
+        }
+
+        if self.kind != SimulationFieldKind::ReadOnly
+            && (self.value < self.min || self.value > self.max)
+        {
+            return Err(format!(
+                "value {} is outside of {}..={}",
+                self.value, self.min, self.max
+            ));
+        }
+
+        Ok(())
+    }
 }
 
 impl core::fmt::Display for SimulationField {
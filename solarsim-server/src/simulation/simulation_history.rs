@@ -0,0 +1,238 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use super::{
+    AmbientTempDriver, SimulationClock, SimulationConfig, SimulationStats, SolarIrradianceDriver,
+};
+
+/// Steps between two history entries, unless configured otherwise.
+pub const HISTORY_INTERVAL_STEPS: u64 = 1;
+/// Memory the history may use, measured in MiB, unless configured otherwise.
+pub const HISTORY_BUDGET_MIB: u64 = 64;
+
+/// State of the simulation at a past step.
+#[derive(Clone)]
+pub struct HistoryEntry {
+    pub clock: SimulationClock,
+    pub config: SimulationConfig,
+    pub stats: SimulationStats,
+    pub solar_irradiance_driver: SolarIrradianceDriver,
+    pub ambient_temp_driver: AmbientTempDriver,
+}
+
+/// Bounded record of past simulation states, to scrub through and rewind to.
+///
+/// An entry is recorded before every `interval` steps, the oldest entries are dropped once the
+/// entries would use more than `budget_bytes`. The history is disabled when the budget is zero,
+/// which is the default so that batch runs don't pay for it.
+#[derive(Resource, Default)]
+pub struct SimulationHistory {
+    entries: VecDeque<HistoryEntry>,
+    interval: u64,
+    budget_bytes: usize,
+    used_bytes: usize,
+}
+
+/// Extent of the history returned by `simulation.history`.
+#[derive(Serialize)]
+pub struct HistorySummary {
+    pub interval: u64,
+    pub count: usize,
+    /// Step of the oldest entry
+    pub first_step: Option<u64>,
+    /// Step of the newest entry
+    pub last_step: Option<u64>,
+    pub used_bytes: usize,
+    pub budget_bytes: usize,
+}
+
+impl HistoryEntry {
+    fn capture(world: &World) -> Self {
+        Self {
+            clock: world.resource::<SimulationClock>().clone(),
+            config: world.resource::<SimulationConfig>().clone(),
+            stats: world.resource::<SimulationStats>().clone(),
+            solar_irradiance_driver: world.resource::<SolarIrradianceDriver>().clone(),
+            ambient_temp_driver: world.resource::<AmbientTempDriver>().clone(),
+        }
+    }
+
+    /// Puts the simulation back in the state of this entry.
+    ///
+    /// The entries recorded after it stay in the history, to keep scrubbing through them, until
+    /// the simulation runs again from there.
+    pub fn restore(self, world: &mut World) {
+        world.insert_resource(self.clock);
+        world.insert_resource(self.config);
+        world.insert_resource(self.stats);
+        world.insert_resource(self.solar_irradiance_driver);
+        world.insert_resource(self.ambient_temp_driver);
+    }
+
+    /// Approximate memory used by the entry, measured in bytes.
+    fn size_bytes(&self) -> usize {
+        let cloud_cover_bytes = match &self.solar_irradiance_driver {
+            SolarIrradianceDriver::CloudCover(cloud_cover) => cloud_cover
+                .conditions
+                .iter()
+                .map(|condition| {
+                    size_of_val(condition) + size_of_val(condition.transitions.as_slice())
+                })
+                .sum(),
+            _ => 0,
+        };
+
+        size_of::<Self>() + cloud_cover_bytes
+    }
+}
+
+impl SimulationHistory {
+    pub fn new(interval: u64, budget_bytes: usize) -> Self {
+        Self {
+            interval: interval.max(1),
+            budget_bytes,
+            ..default()
+        }
+    }
+
+    /// An empty history with the same interval and a `shares`-th of the budget, for histories
+    /// sharing the budget of this one.
+    pub fn empty_share(&self, shares: usize) -> Self {
+        Self::new(self.interval, self.budget_bytes / shares.max(1))
+    }
+
+    #[cfg(any(feature = "gui", test))]
+    pub fn entries(&self) -> &VecDeque<HistoryEntry> {
+        &self.entries
+    }
+
+    /// Index of the newest entry recorded at or before `step`.
+    pub fn index_at(&self, step: u64) -> Option<usize> {
+        self.entries
+            .partition_point(|entry| entry.clock.steps <= step)
+            .checked_sub(1)
+    }
+
+    pub fn summary(&self) -> HistorySummary {
+        HistorySummary {
+            interval: self.interval,
+            count: self.entries.len(),
+            first_step: self.entries.front().map(|entry| entry.clock.steps),
+            last_step: self.entries.back().map(|entry| entry.clock.steps),
+            used_bytes: self.used_bytes,
+            budget_bytes: self.budget_bytes,
+        }
+    }
+
+    fn push(&mut self, entry: HistoryEntry) {
+        // After a rewind, the entries ahead of the restored state belong to a discarded run.
+        while let Some(last) = self.entries.back()
+            && last.clock.steps >= entry.clock.steps
+        {
+            self.used_bytes -= last.size_bytes();
+            self.entries.pop_back();
+        }
+
+        let size_bytes = entry.size_bytes();
+
+        if size_bytes > self.budget_bytes {
+            return;
+        }
+
+        while self.used_bytes + size_bytes > self.budget_bytes
+            && let Some(first) = self.entries.pop_front()
+        {
+            self.used_bytes -= first.size_bytes();
+        }
+
+        self.used_bytes += size_bytes;
+        self.entries.push_back(entry);
+    }
+}
+
+/// Rewinds the simulation to the newest entry recorded at or before `step`, returning its clock.
+pub fn rewind(world: &mut World, step: u64) -> Option<SimulationClock> {
+    let history = world.get_resource::<SimulationHistory>()?;
+    let entry = history.entries[history.index_at(step)?].clone();
+    let clock = entry.clock.clone();
+
+    entry.restore(world);
+
+    Some(clock)
+}
+
+/// Records the state the next step starts from.
+pub(super) fn record_history(world: &mut World) {
+    let steps = world.resource::<SimulationClock>().steps;
+
+    match world.get_resource::<SimulationHistory>() {
+        Some(history) if history.budget_bytes > 0 && steps.is_multiple_of(history.interval) => {}
+        _ => return,
+    }
+
+    let entry = HistoryEntry::capture(world);
+
+    world.resource_mut::<SimulationHistory>().push(entry);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FIXED_TIMESTEP_SECS, simulation::SimulationRunner};
+
+    use super::*;
+
+    fn runner(interval: u64, budget_bytes: usize) -> SimulationRunner {
+        let mut runner = SimulationRunner::new(FIXED_TIMESTEP_SECS);
+
+        runner
+            .app_mut()
+            .insert_resource(SimulationHistory::new(interval, budget_bytes));
+        runner
+    }
+
+    fn steps(world: &World) -> Vec<u64> {
+        world
+            .resource::<SimulationHistory>()
+            .entries()
+            .iter()
+            .map(|entry| entry.clock.steps)
+            .collect()
+    }
+
+    #[test]
+    fn oldest_entries_are_dropped_to_stay_within_budget() {
+        let entry_bytes = size_of::<HistoryEntry>();
+        let mut runner = runner(2, 3 * entry_bytes);
+
+        runner.run(10);
+
+        assert_eq!(steps(runner.world()), [4, 6, 8]);
+        assert_eq!(
+            runner.world().resource::<SimulationHistory>().used_bytes,
+            3 * entry_bytes
+        );
+    }
+
+    #[test]
+    fn running_after_a_rewind_replaces_the_later_entries() {
+        let mut runner = runner(1, usize::MAX);
+
+        runner.run(10);
+
+        let tank_temp = |world: &World| *world.resource::<SimulationConfig>().tank_average_temp;
+        let recorded_temp = *runner.world().resource::<SimulationHistory>().entries()[4]
+            .config
+            .tank_average_temp;
+        let clock = rewind(runner.app_mut().world_mut(), 4).unwrap();
+
+        assert_eq!(clock.steps, 4);
+        assert_eq!(tank_temp(runner.world()), recorded_temp);
+        assert_eq!(steps(runner.world()).len(), 10);
+
+        runner.run(2);
+
+        assert_eq!(steps(runner.world()), [0, 1, 2, 3, 4, 5]);
+    }
+}
@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Whether the simulation systems run on [`FixedUpdate`].
+#[derive(Resource, Reflect, Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub enum SimulationRunState {
+    /// A step runs on every fixed timestep.
+    #[default]
+    Running,
+    /// No steps run, parameters can still be edited.
+    Paused,
+    /// The given number of steps will run before pausing.
+    Stepping(u32),
+}
+
+impl SimulationRunState {
+    #[cfg(any(feature = "gui", test))]
+    pub fn is_paused(&self) -> bool {
+        *self == Self::Paused
+    }
+
+    /// Pauses a running simulation or resumes a paused one.
+    #[cfg(any(feature = "gui", test))]
+    pub fn toggle_pause(&mut self) {
+        *self = match self {
+            Self::Paused => Self::Running,
+            Self::Running | Self::Stepping(_) => Self::Paused,
+        };
+    }
+
+    /// Runs `steps` more steps and pauses afterwards.
+    pub fn step(&mut self, steps: u32) {
+        if steps == 0 {
+            return;
+        }
+
+        *self = match self {
+            Self::Stepping(remaining) => Self::Stepping(remaining.saturating_add(steps)),
+            Self::Running | Self::Paused => Self::Stepping(steps),
+        };
+    }
+}
+
+/// Run condition for the simulation systems.
+pub(super) fn simulation_running(state: Res<SimulationRunState>) -> bool {
+    match *state {
+        SimulationRunState::Running => true,
+        SimulationRunState::Paused => false,
+        SimulationRunState::Stepping(remaining) => remaining > 0,
+    }
+}
+
+/// Counts down the steps left while stepping and pauses once there are none left.
+pub(super) fn finish_step(mut state: ResMut<SimulationRunState>) {
+    if let SimulationRunState::Stepping(remaining) = *state {
+        *state = match remaining {
+            0 | 1 => SimulationRunState::Paused,
+            remaining => SimulationRunState::Stepping(remaining - 1),
+        };
+    }
+}
@@ -0,0 +1,106 @@
+use core::time::Duration;
+
+use bevy::{ecs::schedule::ExecutorKind, prelude::*};
+
+/// Runs the simulation outside of the app loop, one fixed step after another with no rendering
+/// or frame pacing.
+///
+/// The steps run the same [`FixedUpdate`] systems as the interactive server, so both produce the
+/// same results for the same inputs.
+pub struct SimulationRunner {
+    app: App,
+    timestep: Duration,
+}
+
+impl SimulationRunner {
+    pub fn new(timestep_secs: f64) -> Self {
+        let timestep = Duration::from_secs_f64(timestep_secs);
+        let mut app = App::new();
+
+        app.add_plugins(super::plugin);
+        app.insert_resource(Time::<Fixed>::from_duration(timestep));
+        // The step systems form a single chain, spreading them across threads only adds overhead.
+        app.edit_schedule(FixedUpdate, |schedule| {
+            schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        });
+
+        Self { app, timestep }
+    }
+
+    pub fn timestep(&self) -> Duration {
+        self.timestep
+    }
+
+    pub fn world(&self) -> &World {
+        self.app.world()
+    }
+
+    /// The app holding the simulation, to add plugins or insert resources before running it.
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    /// Runs a single simulation step.
+    pub fn step(&mut self) {
+        let world = self.app.world_mut();
+
+        world
+            .resource_mut::<Time<Fixed>>()
+            .advance_by(self.timestep);
+        world.run_schedule(FixedUpdate);
+    }
+
+    /// Runs `steps` simulation steps.
+    pub fn run(&mut self, steps: u64) {
+        for _ in 0..steps {
+            self.step();
+        }
+    }
+
+    /// Number of steps needed to cover `duration_secs` of simulated time.
+    pub fn steps_for(&self, duration_secs: f64) -> u64 {
+        (duration_secs / self.timestep.as_secs_f64()).ceil() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        FIXED_TIMESTEP_SECS,
+        simulation::{SimulationClock, SimulationConfig},
+    };
+
+    use super::*;
+
+    #[test]
+    fn runner_matches_fixed_update_app() {
+        let mut runner = SimulationRunner::new(FIXED_TIMESTEP_SECS);
+        let mut app = App::new();
+
+        app.add_plugins(crate::simulation::plugin);
+        app.insert_resource(Time::<Fixed>::from_seconds(FIXED_TIMESTEP_SECS));
+
+        for _ in 0..100 {
+            runner.step();
+            app.world_mut()
+                .resource_mut::<Time<Fixed>>()
+                .advance_by(Duration::from_secs_f64(FIXED_TIMESTEP_SECS));
+            app.world_mut().run_schedule(FixedUpdate);
+        }
+
+        let tank_temp = |world: &World| *world.resource::<SimulationConfig>().tank_average_temp;
+
+        assert_eq!(runner.world().resource::<SimulationClock>().steps, 100);
+        assert_eq!(tank_temp(runner.world()), tank_temp(app.world()));
+    }
+
+    #[test]
+    fn steps_cover_the_whole_duration() {
+        let runner = SimulationRunner::new(0.5);
+
+        assert_eq!(runner.steps_for(3600.0), 7200);
+        assert_eq!(runner.steps_for(3600.2), 7201);
+    }
+}
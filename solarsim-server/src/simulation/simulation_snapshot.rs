@@ -0,0 +1,47 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    AmbientTempDriver, HorizonProfile, SimulationClock, SimulationConfig, SimulationRunner,
+    SolarIrradianceDriver,
+};
+
+/// Inputs of the simulation at a point in time, everything needed to run it again from there.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SimulationSnapshot {
+    pub timestep_secs: f64,
+    pub config: SimulationConfig,
+    pub clock: SimulationClock,
+    pub solar_irradiance_driver: SolarIrradianceDriver,
+    pub ambient_temp_driver: AmbientTempDriver,
+    pub horizon_profile: HorizonProfile,
+}
+
+impl SimulationSnapshot {
+    pub fn capture(world: &World) -> Self {
+        Self {
+            timestep_secs: world.resource::<Time<Fixed>>().timestep().as_secs_f64(),
+            config: world.resource::<SimulationConfig>().clone(),
+            clock: world.resource::<SimulationClock>().clone(),
+            solar_irradiance_driver: world.resource::<SolarIrradianceDriver>().clone(),
+            ambient_temp_driver: world.resource::<AmbientTempDriver>().clone(),
+            horizon_profile: world.resource::<HorizonProfile>().clone(),
+        }
+    }
+
+    /// A runner starting from this snapshot.
+    pub fn runner(&self) -> SimulationRunner {
+        let mut runner = SimulationRunner::new(self.timestep_secs);
+        let snapshot = self.clone();
+
+        runner
+            .app_mut()
+            .insert_resource(snapshot.config)
+            .insert_resource(snapshot.clock)
+            .insert_resource(snapshot.solar_irradiance_driver)
+            .insert_resource(snapshot.ambient_temp_driver)
+            .insert_resource(snapshot.horizon_profile);
+
+        runner
+    }
+}
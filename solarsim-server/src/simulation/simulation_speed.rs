@@ -0,0 +1,121 @@
+use core::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Upper bound of simulation steps run in a single frame.
+///
+/// This keeps the app responsive at high speeds, the simulation is reported as lagging when the
+/// requested speed needs more steps than this.
+pub const MAX_STEPS_PER_FRAME: u32 = 1000;
+
+/// Relative speed of the simulated time with respect to the wall-clock time.
+///
+/// The simulation step size is not affected by the speed, higher speeds run more steps per frame.
+#[derive(Resource, Reflect, Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq)]
+#[reflect(Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimulationSpeed {
+    /// Requested speed
+    pub target: SpeedTarget,
+    /// Simulated seconds per wall-clock second achieved during the last frame
+    pub achieved: f64,
+    /// Whether the simulation could not keep up with the requested speed during the last frame
+    pub lagging: bool,
+}
+
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[reflect(Serialize, Deserialize)]
+pub enum SpeedTarget {
+    /// Simulated seconds per wall-clock second.
+    Multiplier(f64),
+    /// Run [`MAX_STEPS_PER_FRAME`] steps every frame.
+    Max,
+}
+
+impl SpeedTarget {
+    /// Speeds offered in the UI.
+    #[cfg(feature = "gui")]
+    pub const PRESETS: [Self; 4] = [
+        Self::Multiplier(1.0),
+        Self::Multiplier(60.0),
+        Self::Multiplier(3600.0),
+        Self::Max,
+    ];
+}
+
+impl Default for SpeedTarget {
+    fn default() -> Self {
+        Self::Multiplier(1.0)
+    }
+}
+
+impl core::fmt::Display for SpeedTarget {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Multiplier(multiplier) => write!(f, "{multiplier}×"),
+            Self::Max => write!(f, "Max"),
+        }
+    }
+}
+
+/// Records the speed achieved during the last frame.
+pub(super) fn track_simulation_speed(
+    real_time: Res<Time<Real>>,
+    virtual_time: Res<Time<Virtual>>,
+    mut speed: ResMut<SimulationSpeed>,
+) {
+    let real_delta = real_time.delta_secs_f64();
+
+    if real_delta <= 0.0 {
+        return;
+    }
+
+    let virtual_delta = virtual_time.delta_secs_f64();
+    let achieved = virtual_delta / real_delta;
+    let lagging = match speed.target {
+        // Allow for rounding errors in the virtual clock.
+        SpeedTarget::Multiplier(multiplier) => achieved < multiplier * 0.99,
+        SpeedTarget::Max => false,
+    };
+
+    if lagging && !speed.lagging {
+        warn!(
+            "Simulation can't keep up with {} (achieved {achieved:.0}×)",
+            speed.target
+        );
+    }
+
+    // Avoid triggering change detection on every frame.
+    speed.bypass_change_detection().achieved = achieved;
+    speed.set_if_neq(SimulationSpeed { lagging, ..*speed });
+}
+
+/// Applies the requested speed to the virtual clock that drives [`FixedUpdate`].
+///
+/// Bevy clamps the wall-clock time of a frame to the maximum delta before scaling it by the
+/// relative speed, so the maximum delta is scaled down by the speed to cap the simulated time, and
+/// so the steps, of a frame. The time beyond it is dropped, which shows as a lower achieved speed.
+pub(super) fn apply_simulation_speed(
+    speed: Res<SimulationSpeed>,
+    fixed_time: Res<Time<Fixed>>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+) {
+    let max_steps_delta = fixed_time.timestep() * MAX_STEPS_PER_FRAME;
+    // Any speed that always hits the maximum delta, higher ones are capped to it.
+    let max_speed = f64::from(MAX_STEPS_PER_FRAME) * 1000.0;
+    let relative_speed = match speed.target {
+        // `max` and `min` also turn NaN into 0.
+        SpeedTarget::Multiplier(multiplier) => multiplier.max(0.0).min(max_speed),
+        SpeedTarget::Max => max_speed,
+    };
+    // Tiny speeds give a maximum delta too long for a `Duration`, which isn't reached anyway.
+    let max_delta = match relative_speed > 0.0 {
+        true => Duration::try_from_secs_f64(max_steps_delta.as_secs_f64() / relative_speed)
+            .unwrap_or(max_steps_delta),
+        false => max_steps_delta,
+    };
+
+    virtual_time.set_max_delta(max_delta);
+    virtual_time.set_relative_speed_f64(relative_speed);
+}
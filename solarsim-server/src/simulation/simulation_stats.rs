@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Joules in a kilowatt-hour.
+pub(super) const JOULES_PER_KWH: f64 = 3.6e6;
+
+/// Tank temperature above which the system counts as overheating, measured in °C.
+pub const OVERHEATING_TEMP: f32 = 80.0;
+
+/// Energy terms accumulated since the simulation started.
+#[derive(Resource, Reflect, Serialize, Deserialize, Clone, Default, Debug)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct SimulationStats {
+    /// Energy the auxiliary heater added to bring the load up to its delivery temperature, measured in kWh
+    pub aux_energy: f64,
+    /// Heat lost through the panel, pipes and tank, measured in kWh
+    pub heat_loss: f64,
+    /// Energy needed to heat the load from its inlet to its delivery temperature, measured in kWh
+    pub load_demand: f64,
+    /// Time the tank spent above [`OVERHEATING_TEMP`], measured in hours
+    pub overheating_hours: f64,
+    /// Solar energy the collector could not capture due to shading, measured in kWh
+    pub shading_loss: f64,
+    /// Solar energy captured by the collector, measured in kWh
+    pub solar_gain: f64,
+}
+
+impl SimulationStats {
+    /// Share of the load demand covered by solar energy (0.0-1.0).
+    pub fn solar_fraction(&self) -> f64 {
+        if self.load_demand > 0.0 {
+            (1.0 - self.aux_energy / self.load_demand).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solar_fraction_is_share_of_demand_not_covered_by_aux() {
+        let stats = SimulationStats {
+            aux_energy: 25.0,
+            load_demand: 100.0,
+            ..default()
+        };
+
+        assert_eq!(stats.solar_fraction(), 0.75);
+    }
+
+    #[test]
+    fn solar_fraction_without_demand_is_zero() {
+        assert_eq!(SimulationStats::default().solar_fraction(), 0.0);
+    }
+}
@@ -0,0 +1,81 @@
+/// Position of the sun in the sky, in degrees.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SolarPosition {
+    /// Measured clockwise from north (0-360)
+    pub azimuth: f32,
+    /// Measured from the horizontal plane, negative when the sun has set
+    pub elevation: f32,
+}
+
+impl SolarPosition {
+    /// Computes the sun position using Cooper's declination and the hour angle.
+    ///
+    /// `day_of_year` starts at 1 for January 1st and `solar_hour` is the local solar time in
+    /// hours, where 12.0 is solar noon.
+    pub fn new(latitude: f32, day_of_year: f32, solar_hour: f32) -> Self {
+        let latitude = latitude.to_radians();
+        let declination = declination(day_of_year);
+        let hour_angle = (15.0 * (solar_hour - 12.0)).to_radians();
+
+        let sin_elevation = latitude.sin() * declination.sin()
+            + latitude.cos() * declination.cos() * hour_angle.cos();
+        let elevation = sin_elevation.clamp(-1.0, 1.0).asin();
+
+        let cos_azimuth = (declination.sin() * latitude.cos()
+            - declination.cos() * latitude.sin() * hour_angle.cos())
+            / elevation.cos().max(f32::EPSILON);
+        let azimuth = cos_azimuth.clamp(-1.0, 1.0).acos().to_degrees();
+
+        Self {
+            // The sun is west of the meridian in the afternoon.
+            azimuth: if hour_angle > 0.0 {
+                360.0 - azimuth
+            } else {
+                azimuth
+            },
+            elevation: elevation.to_degrees(),
+        }
+    }
+}
+
+/// Solar declination in radians.
+fn declination(day_of_year: f32) -> f32 {
+    (23.45 * (360.0 / 365.0 * (284.0 + day_of_year)).to_radians().sin()).to_radians()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equinox_noon_at_equator_is_overhead() {
+        let position = SolarPosition::new(0.0, 81.0, 12.0);
+
+        assert!(position.elevation > 89.0);
+    }
+
+    #[test]
+    fn summer_noon_is_due_south_in_the_north() {
+        let position = SolarPosition::new(40.0, 172.0, 12.0);
+
+        assert!((position.azimuth - 180.0).abs() < 1.0);
+        assert!((position.elevation - 73.45).abs() < 0.1);
+    }
+
+    #[test]
+    fn morning_sun_is_east_and_afternoon_sun_is_west() {
+        let morning = SolarPosition::new(40.0, 172.0, 9.0);
+        let afternoon = SolarPosition::new(40.0, 172.0, 15.0);
+
+        assert!(morning.azimuth < 180.0);
+        assert!(afternoon.azimuth > 180.0);
+        assert!((morning.elevation - afternoon.elevation).abs() < 1e-3);
+    }
+
+    #[test]
+    fn sun_is_below_horizon_at_midnight() {
+        let position = SolarPosition::new(40.0, 172.0, 0.0);
+
+        assert!(position.elevation < 0.0);
+    }
+}
@@ -0,0 +1,404 @@
+use std::{collections::BTreeMap, io, time::Instant};
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    batch::{BatchStats, write_output},
+    cli::{BatchFormat, SweepArgs, SweepParam},
+    simulation::{SimulationConfig, SimulationSnapshot, SimulationStats},
+    utils::{default_jobs, parallel_map},
+};
+
+#[derive(Debug)]
+pub enum SweepError {
+    UnknownField(String),
+    /// A swept value is outside of the range of its field.
+    OutOfRange {
+        field: String,
+        value: f32,
+        min: f32,
+        max: f32,
+    },
+    UnknownMetric(String),
+    /// The results could not be written.
+    Output(io::Error),
+}
+
+impl core::fmt::Display for SweepError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnknownField(field) => write!(f, "Unknown simulation field {field}"),
+            Self::OutOfRange {
+                field,
+                value,
+                min,
+                max,
+            } => write!(f, "Value {value} of {field} is outside of {min}..={max}"),
+            Self::UnknownMetric(metric) => write!(
+                f,
+                "Unknown metric {metric}, expected one of {}",
+                metric_names().join(", ")
+            ),
+            Self::Output(error) => write!(f, "Unable to write results: {error}"),
+        }
+    }
+}
+
+impl core::error::Error for SweepError {}
+
+/// Results of a run with one combination of field values.
+#[derive(Serialize)]
+struct SweepRun {
+    /// Value of every swept field
+    fields: BTreeMap<String, f32>,
+    stats: BatchStats,
+}
+
+/// Effect on the metric of moving one field from its lowest to its highest swept value, with
+/// the other fields at their baseline.
+#[derive(Serialize)]
+struct SensitivityIndex {
+    field: String,
+    baseline_value: f32,
+    low_value: f32,
+    high_value: f32,
+    low_metric: f64,
+    high_metric: f64,
+    /// `high_metric - low_metric`
+    swing: f64,
+    /// Relative change of the metric over the relative change of the field, `None` when either
+    /// baseline is zero
+    elasticity: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct SensitivityResults {
+    metric: String,
+    /// Metric with every field at its baseline
+    baseline: f64,
+    /// Sorted by decreasing absolute swing
+    indices: Vec<SensitivityIndex>,
+}
+
+/// Runs the simulation from `snapshot` for every combination of the swept field values, or for
+/// every field one at a time in sensitivity mode, and writes the results.
+pub fn run(snapshot: &SimulationSnapshot, args: &SweepArgs) -> Result<(), SweepError> {
+    for param in &args.params {
+        validate(&snapshot.config, param)?;
+    }
+
+    if !metric_names().contains(&args.metric) {
+        return Err(SweepError::UnknownMetric(args.metric.clone()));
+    }
+
+    let combinations = if args.sensitivity {
+        one_at_a_time(&args.params)
+    } else {
+        grid(&args.params)
+    };
+    let jobs = args.jobs.unwrap_or_else(default_jobs);
+    let started_at = Instant::now();
+    let stats = parallel_map(&combinations, jobs, |fields| {
+        BatchStats::new(&simulate(snapshot, args.duration, fields).1)
+    });
+
+    info!(
+        "Simulated {} runs of {}s on {jobs} threads in {:.2?}",
+        combinations.len(),
+        args.duration,
+        started_at.elapsed()
+    );
+
+    let output = if args.sensitivity {
+        let results = sensitivity(&snapshot.config, &args.params, &args.metric, &stats);
+
+        match args.format {
+            BatchFormat::Csv => sensitivity_to_csv(&results),
+            BatchFormat::Json => serde_json::to_string_pretty(&results).map_err(io::Error::other),
+        }
+    } else {
+        let runs = combinations
+            .into_iter()
+            .zip(stats)
+            .map(|(fields, stats)| SweepRun {
+                fields: fields.into_iter().collect(),
+                stats,
+            })
+            .collect::<Vec<_>>();
+
+        match args.format {
+            BatchFormat::Csv => runs_to_csv(&args.params, &runs),
+            BatchFormat::Json => serde_json::to_string_pretty(&serde_json::json!({ "runs": runs }))
+                .map_err(io::Error::other),
+        }
+    }
+    .map_err(SweepError::Output)?;
+
+    write_output(args.output.as_deref(), &output).map_err(SweepError::Output)
+}
+
+fn validate(config: &SimulationConfig, param: &SweepParam) -> Result<(), SweepError> {
+    let field = config
+        .field(&param.field)
+        .ok_or_else(|| SweepError::UnknownField(param.field.clone()))?;
+
+    match param
+        .values
+        .iter()
+        .find(|value| !(field.min()..=field.max()).contains(*value))
+    {
+        Some(value) => Err(SweepError::OutOfRange {
+            field: param.field.clone(),
+            value: *value,
+            min: field.min(),
+            max: field.max(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Names of the results a sensitivity can be measured on.
+fn metric_names() -> Vec<String> {
+    BatchStats::new(&SimulationStats::default())
+        .metrics()
+        .into_keys()
+        .map(str::to_string)
+        .collect()
+}
+
+fn metric(stats: &BatchStats, name: &str) -> f64 {
+    stats.metrics().get(name).copied().unwrap_or_default()
+}
+
+/// Every combination of the swept values, the last field varying fastest.
+fn grid(params: &[SweepParam]) -> Vec<Vec<(String, f32)>> {
+    params
+        .iter()
+        .fold(vec![Vec::default()], |combinations, param| {
+            combinations
+                .iter()
+                .flat_map(|combination| {
+                    param.values.iter().map(move |value| {
+                        let mut combination = combination.clone();
+
+                        combination.push((param.field.clone(), *value));
+                        combination
+                    })
+                })
+                .collect()
+        })
+}
+
+/// The baseline, with no field changed, followed by the lowest and highest value of every field.
+fn one_at_a_time(params: &[SweepParam]) -> Vec<Vec<(String, f32)>> {
+    let mut combinations = vec![Vec::default()];
+
+    for param in params {
+        let (low, high) = low_and_high(param);
+
+        combinations.push(vec![(param.field.clone(), low)]);
+        combinations.push(vec![(param.field.clone(), high)]);
+    }
+
+    combinations
+}
+
+fn low_and_high(param: &SweepParam) -> (f32, f32) {
+    param
+        .values
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(low, high), value| {
+            (low.min(*value), high.max(*value))
+        })
+}
+
+/// Runs the simulation from `snapshot` with the given field values, returning the final config
+/// and totals.
+pub fn simulate(
+    snapshot: &SimulationSnapshot,
+    duration_secs: f64,
+    fields: &[(String, f32)],
+) -> (SimulationConfig, SimulationStats) {
+    let mut runner = snapshot.runner();
+    let steps = runner.steps_for(duration_secs);
+    let mut config = runner
+        .app_mut()
+        .world_mut()
+        .resource_mut::<SimulationConfig>();
+
+    for (field, value) in fields {
+        config.set_field(field, *value);
+    }
+
+    runner.run(steps);
+
+    let world = runner.world();
+
+    (
+        world.resource::<SimulationConfig>().clone(),
+        world.resource::<SimulationStats>().clone(),
+    )
+}
+
+/// Builds the tornado data from the runs of [`one_at_a_time`].
+fn sensitivity(
+    config: &SimulationConfig,
+    params: &[SweepParam],
+    metric_name: &str,
+    stats: &[BatchStats],
+) -> SensitivityResults {
+    let baseline = metric(&stats[0], metric_name);
+    let mut indices = params
+        .iter()
+        .zip(stats[1..].chunks_exact(2))
+        .map(|(param, stats)| {
+            let (low_value, high_value) = low_and_high(param);
+            let baseline_value = config.field(&param.field).map_or(0.0, |field| **field);
+            let low_metric = metric(&stats[0], metric_name);
+            let high_metric = metric(&stats[1], metric_name);
+            let swing = high_metric - low_metric;
+            let elasticity = (baseline != 0.0 && baseline_value != 0.0 && low_value != high_value)
+                .then(|| {
+                    (swing / baseline)
+                        / (f64::from(high_value - low_value) / f64::from(baseline_value))
+                });
+
+            SensitivityIndex {
+                field: param.field.clone(),
+                baseline_value,
+                low_value,
+                high_value,
+                low_metric,
+                high_metric,
+                swing,
+                elasticity,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    indices.sort_by(|a, b| b.swing.abs().total_cmp(&a.swing.abs()));
+
+    SensitivityResults {
+        metric: metric_name.to_string(),
+        baseline,
+        indices,
+    }
+}
+
+/// One row per run, with the swept field values followed by the energy totals.
+fn runs_to_csv(params: &[SweepParam], runs: &[SweepRun]) -> io::Result<String> {
+    let mut csv = String::default();
+
+    for (index, run) in runs.iter().enumerate() {
+        let stats = run.stats.metrics();
+
+        if index == 0 {
+            let header = params
+                .iter()
+                .map(|param| param.field.as_str())
+                .chain(stats.keys().copied())
+                .collect::<Vec<_>>();
+
+            csv.push_str(&header.join(","));
+            csv.push('\n');
+        }
+
+        let row = params
+            .iter()
+            .map(|param| run.fields[&param.field].to_string())
+            .chain(stats.values().map(f64::to_string))
+            .collect::<Vec<_>>();
+
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    Ok(csv)
+}
+
+/// One row per field, in decreasing order of swing.
+fn sensitivity_to_csv(results: &SensitivityResults) -> io::Result<String> {
+    let mut csv =
+        "field,baseline_value,low_value,high_value,low_metric,high_metric,swing,elasticity\n"
+            .to_string();
+
+    for index in &results.indices {
+        let row = [
+            index.field.clone(),
+            index.baseline_value.to_string(),
+            index.low_value.to_string(),
+            index.high_value.to_string(),
+            index.low_metric.to_string(),
+            index.high_metric.to_string(),
+            index.swing.to_string(),
+            index.elasticity.map(|e| e.to_string()).unwrap_or_default(),
+        ];
+
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    Ok(csv)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FIXED_TIMESTEP_SECS, simulation::SimulationRunner};
+
+    use super::*;
+
+    fn param(field: &str, values: &[f32]) -> SweepParam {
+        SweepParam {
+            field: field.to_string(),
+            values: values.to_vec(),
+        }
+    }
+
+    #[test]
+    fn grid_covers_every_combination() {
+        let combinations = grid(&[param("a", &[1.0, 2.0]), param("b", &[3.0, 4.0, 5.0])]);
+
+        assert_eq!(combinations.len(), 6);
+        assert_eq!(
+            combinations[0],
+            [("a".to_string(), 1.0), ("b".to_string(), 3.0)]
+        );
+        assert_eq!(
+            combinations[5],
+            [("a".to_string(), 2.0), ("b".to_string(), 5.0)]
+        );
+    }
+
+    #[test]
+    fn fields_are_validated_against_their_range() {
+        let config = SimulationConfig::default();
+
+        assert!(validate(&config, &param("panel_area", &[2.0, 3.0])).is_ok());
+        assert!(matches!(
+            validate(&config, &param("panel_area", &[100.0])),
+            Err(SweepError::OutOfRange { .. })
+        ));
+        assert!(matches!(
+            validate(&config, &param("panel_size", &[2.0])),
+            Err(SweepError::UnknownField(_))
+        ));
+    }
+
+    #[test]
+    fn larger_panels_gain_more_solar_energy() {
+        let snapshot =
+            SimulationSnapshot::capture(SimulationRunner::new(FIXED_TIMESTEP_SECS).world());
+        let params = [param("panel_area", &[1.5, 3.0])];
+        let combinations = one_at_a_time(&params);
+        let stats = parallel_map(&combinations, 2, |fields| {
+            BatchStats::new(&simulate(&snapshot, 3600.0, fields).1)
+        });
+        let results = sensitivity(&snapshot.config, &params, "solar_gain", &stats);
+        let index = &results.indices[0];
+
+        assert_eq!(stats.len(), 3);
+        assert!(index.high_metric > index.low_metric);
+        assert!(index.elasticity.is_some_and(|elasticity| elasticity > 0.0));
+    }
+}
@@ -0,0 +1,132 @@
+use bevy::{
+    ecs::spawn::SpawnIter,
+    prelude::*,
+    ui::Checked,
+    ui_widgets::{Activate, observe},
+};
+
+use crate::{
+    simulation::{SimulationRunState, SimulationSpeed, SpeedTarget},
+    ui::widgets::button,
+};
+
+/// Key that pauses or resumes the simulation.
+const PAUSE_KEY: KeyCode = KeyCode::KeyP;
+/// Key that runs a single step of the simulation.
+const STEP_KEY: KeyCode = KeyCode::Period;
+
+/// Speed selected by a button.
+#[derive(Component)]
+struct SpeedButton(SpeedTarget);
+
+#[derive(Component)]
+struct PauseButton;
+
+#[derive(Component)]
+struct SpeedStatusText;
+
+/// Row of buttons controlling how the simulation runs.
+pub(super) fn controls() -> impl Bundle {
+    (
+        Node {
+            align_items: AlignItems::Center,
+            column_gap: px(8),
+            ..default()
+        },
+        Children::spawn((
+            Spawn((
+                PauseButton,
+                button("Pause (P)"),
+                observe(|_: On<Activate>, mut state: ResMut<SimulationRunState>| {
+                    state.toggle_pause();
+                }),
+            )),
+            Spawn((
+                button("Step (.)"),
+                observe(|_: On<Activate>, mut state: ResMut<SimulationRunState>| {
+                    state.step(1);
+                }),
+            )),
+            SpawnIter(SpeedTarget::PRESETS.into_iter().map(|target| {
+                (
+                    SpeedButton(target),
+                    button(target.to_string()),
+                    observe(
+                        |activate: On<Activate>,
+                         buttons: Query<&SpeedButton>,
+                         mut speed: ResMut<SimulationSpeed>| {
+                            if let Ok(SpeedButton(target)) = buttons.get(activate.entity) {
+                                speed.target = *target;
+                            }
+                        },
+                    ),
+                )
+            })),
+            Spawn((
+                SpeedStatusText,
+                Text::default(),
+                TextFont::from_font_size(14.0),
+            )),
+        )),
+    )
+}
+
+fn update_speed_controls(
+    speed: Res<SimulationSpeed>,
+    buttons: Query<(Entity, &SpeedButton)>,
+    mut texts: Query<&mut Text, With<SpeedStatusText>>,
+    mut commands: Commands,
+) {
+    for (button_ent, SpeedButton(target)) in buttons.iter() {
+        if *target == speed.target {
+            commands.entity(button_ent).insert(Checked);
+        } else {
+            commands.entity(button_ent).remove::<Checked>();
+        }
+    }
+
+    for mut text in texts.iter_mut() {
+        **text = match speed.lagging {
+            true => format!("Can't keep up (achieved {:.0}×)", speed.achieved),
+            false => String::default(),
+        };
+    }
+}
+
+fn update_run_state_controls(
+    state: Res<SimulationRunState>,
+    buttons: Query<Entity, With<PauseButton>>,
+    mut commands: Commands,
+) {
+    for button_ent in buttons.iter() {
+        if state.is_paused() {
+            commands.entity(button_ent).insert(Checked);
+        } else {
+            commands.entity(button_ent).remove::<Checked>();
+        }
+    }
+}
+
+fn handle_run_state_shortcuts(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<SimulationRunState>,
+) {
+    if keys.just_pressed(PAUSE_KEY) {
+        state.toggle_pause();
+    }
+
+    if keys.just_pressed(STEP_KEY) {
+        state.step(1);
+    }
+}
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            handle_run_state_shortcuts,
+            update_run_state_controls.run_if(resource_changed::<SimulationRunState>),
+            update_speed_controls.run_if(resource_changed::<SimulationSpeed>),
+        ),
+    );
+}
@@ -0,0 +1,124 @@
+use bevy::{
+    prelude::*,
+    ui_widgets::{SliderRange, SliderValue, ValueChange, observe},
+};
+
+use crate::{
+    simulation::{InputRecorder, SimulationClock, SimulationHistory, SimulationRunState},
+    ui::widgets::slider,
+};
+
+const BYTES_PER_MIB: f64 = 1024.0 * 1024.0;
+
+/// Slider over the history entries, from the oldest to the newest.
+#[derive(Component)]
+struct HistorySlider;
+
+#[derive(Component)]
+struct HistoryText;
+
+/// Row with a slider to scrub through the simulation history.
+///
+/// Scrubbing pauses the simulation, which continues from the selected entry when resumed. It is
+/// ignored while recording.
+pub(super) fn history_controls(history: &SimulationHistory) -> impl Bundle {
+    (
+        Node {
+            align_items: AlignItems::Center,
+            column_gap: px(8),
+            ..default()
+        },
+        children![
+            (Text::new("History"), TextFont::from_font_size(16.0)),
+            (
+                Node {
+                    width: px(320),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Stretch,
+                    ..default()
+                },
+                children![(
+                    HistorySlider,
+                    slider(0.0, 0.0, 0.0, 0),
+                    observe(
+                        |value_change: On<ValueChange<f32>>,
+                         history: Res<SimulationHistory>,
+                         recorder: Option<Res<InputRecorder>>,
+                         mut state: ResMut<SimulationRunState>,
+                         mut commands: Commands| {
+                            if recorder.is_some() {
+                                warn!("The simulation can't be rewound while recording");
+                                return;
+                            }
+
+                            let index = value_change.value.round() as usize;
+
+                            if let Some(entry) = history.entries().get(index).cloned() {
+                                *state = SimulationRunState::Paused;
+                                commands.queue(move |world: &mut World| entry.restore(world));
+                            }
+                        },
+                    ),
+                )],
+            ),
+            (
+                HistoryText,
+                Text::new(history_label(history)),
+                TextFont::from_font_size(14.0),
+            ),
+        ],
+    )
+}
+
+fn history_label(history: &SimulationHistory) -> String {
+    let summary = history.summary();
+
+    if summary.budget_bytes == 0 {
+        return "Disabled".to_string();
+    }
+
+    format!(
+        "{} entries, {:.1} of {:.0} MiB",
+        summary.count,
+        summary.used_bytes as f64 / BYTES_PER_MIB,
+        summary.budget_bytes as f64 / BYTES_PER_MIB
+    )
+}
+
+/// Moves the slider to the entry of the current step and extends it over new entries.
+fn update_history_controls(
+    history: Res<SimulationHistory>,
+    clock: Res<SimulationClock>,
+    sliders: Query<(Entity, &SliderValue, &SliderRange), With<HistorySlider>>,
+    mut texts: Query<&mut Text, With<HistoryText>>,
+    mut commands: Commands,
+) {
+    let end = history.entries().len().saturating_sub(1) as f32;
+    let value = history
+        .index_at(clock.steps)
+        .map_or(0.0, |index| index as f32);
+
+    for (slider_ent, slider_value, slider_range) in sliders.iter() {
+        if slider_range.end() != end {
+            commands
+                .entity(slider_ent)
+                .insert(SliderRange::new(0.0, end));
+        }
+
+        if slider_value.0 != value {
+            commands.entity(slider_ent).insert(SliderValue(value));
+        }
+    }
+
+    for mut text in texts.iter_mut() {
+        **text = history_label(&history);
+    }
+}
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        update_history_controls
+            .run_if(resource_changed::<SimulationHistory>.or(resource_changed::<SimulationClock>)),
+    );
+}
@@ -1,15 +1,22 @@
 use bevy::{
     input_focus::tab_navigation::TabGroup,
     prelude::*,
-    ui_widgets::{ValueChange, observe},
+    ui_widgets::{SliderRange, SliderStep, SliderValue, ValueChange, observe},
 };
 
 use crate::{
-    simulation::{SimulationConfig, SimulationField, SimulationFieldKind},
+    simulation::{
+        ActiveScenario, ChangeSource, FieldMetadata, FieldUpdateMode, InputRecorder,
+        RecordedChange, SimulationClock, SimulationConfig, SimulationField, SimulationFieldKind,
+        SimulationHistory, field_metadata,
+    },
     ui::widgets::slider,
     utils::{Maybe, capitalize},
 };
 
+mod controls;
+mod history;
+mod scenario;
 pub mod widgets;
 
 pub fn plugin(app: &mut App) {
@@ -20,20 +27,38 @@ pub fn plugin(app: &mut App) {
         |bytes: &[u8], _path: String| { Font::try_from_bytes(bytes.to_vec()).unwrap() }
     );
 
-    app.add_plugins(widgets::plugin);
+    app.add_plugins((
+        controls::plugin,
+        history::plugin,
+        scenario::plugin,
+        widgets::plugin,
+    ));
 
     app.add_systems(Startup, setup);
 
     app.add_systems(
         Update,
-        update_field_values.run_if(resource_changed::<SimulationConfig>),
+        (
+            (update_field_values, update_slider_values)
+                .run_if(resource_changed::<SimulationConfig>),
+            update_clock_text.run_if(resource_changed::<SimulationClock>),
+        ),
     );
 }
 
 #[derive(Component)]
 struct FieldValueText;
 
-fn setup(mut commands: Commands, cfg: Res<SimulationConfig>) {
+#[derive(Component)]
+struct ClockText;
+
+fn setup(
+    mut commands: Commands,
+    cfg: Res<SimulationConfig>,
+    clock: Res<SimulationClock>,
+    active_scenario: Res<ActiveScenario>,
+    history: Res<SimulationHistory>,
+) {
     commands.spawn(Camera2d);
 
     let mut slider_children = Vec::default();
@@ -43,6 +68,7 @@ fn setup(mut commands: Commands, cfg: Res<SimulationConfig>) {
     if let Ok(s) = cfg.reflect_ref().as_struct() {
         for i in 0..s.field_len() {
             if let Some(name) = s.name_at(i) {
+                let metadata = field_metadata(name);
                 let (min, max, value, kind) = s.field_at(i).map_or(default_field, |f| {
                     f.try_downcast_ref::<SimulationField>()
                         .map_or(default_field, |field| {
@@ -67,13 +93,19 @@ fn setup(mut commands: Commands, cfg: Res<SimulationConfig>) {
                                 },
                                 children![
                                     (
-                                        Text::new(format!("{}: ", capitalize(name))),
+                                        Text::new(format!(
+                                            "{}: ",
+                                            metadata.map_or_else(
+                                                || capitalize(name),
+                                                FieldMetadata::label_with_unit
+                                            )
+                                        )),
                                         TextFont::from_font_size(16.0)
                                     ),
                                     (
                                         FieldValueText,
                                         Name::new(name.to_string()),
-                                        Text::new(value.to_string()),
+                                        Text::new(format_value(name, value)),
                                         TextFont::from_font_size(16.0)
                                     )
                                 ]
@@ -82,17 +114,36 @@ fn setup(mut commands: Commands, cfg: Res<SimulationConfig>) {
                                 Name::new(name.to_string()),
                                 Maybe::new(
                                     (kind == SimulationFieldKind::Slider).then_some((
-                                        slider(min, max, value),
+                                        slider(
+                                            min,
+                                            max,
+                                            value,
+                                            metadata.map_or(2, |metadata| metadata.precision.into())
+                                        ),
+                                        SliderStep(metadata.map_or(1.0, |metadata| metadata.step as f32)),
                                         observe(
                                             |value_change: On<ValueChange<f32>>,
                                             mut cfg: ResMut<SimulationConfig>,
+                                            clock: Res<SimulationClock>,
+                                            recorder: Option<ResMut<InputRecorder>>,
                                             names: Query<&Name>| {
-                                                if let Ok(reflect_struct) = cfg.reflect_mut().as_struct()
-                                                    && let Ok(name) = names.get(value_change.event_target())
-                                                    && let Some(field) = reflect_struct.field_mut(name)
-                                                    && let Some(value) = field.try_downcast_mut::<SimulationField>()
-                                                {
-                                                    **value = value_change.value;
+                                                let Ok(name) = names.get(value_change.event_target()) else {
+                                                    return;
+                                                };
+
+                                                match cfg.update_field(name, value_change.value, FieldUpdateMode::default()) {
+                                                    Ok(change) => {
+                                                        if let Some(mut recorder) = recorder {
+                                                            recorder.record(RecordedChange {
+                                                                step: clock.steps,
+                                                                source: ChangeSource::Ui,
+                                                                field: name.to_string(),
+                                                                old_value: change.old_value,
+                                                                new_value: change.new_value,
+                                                            });
+                                                        }
+                                                    }
+                                                    Err(error) => warn!("{name}: {error}"),
                                                 }
                                             },
                                         ),
@@ -169,7 +220,17 @@ fn setup(mut commands: Commands, cfg: Res<SimulationConfig>) {
                 row_gap: px(20),
                 ..default()
             },
-            children![(Text::new("Solarsim"), TextFont::from_font_size(32.0))],
+            children![
+                (Text::new("Solarsim"), TextFont::from_font_size(32.0)),
+                (
+                    ClockText,
+                    Text::new(clock.to_string()),
+                    TextFont::from_font_size(16.0)
+                ),
+                controls::controls(),
+                history::history_controls(&history),
+                scenario::scenario_controls(&active_scenario)
+            ],
         ))
         .add_child(fields_node_entity);
 }
@@ -183,7 +244,42 @@ fn update_field_values(
             && let Some(field) = reflect_struct.field(name)
             && let Some(value) = field.try_downcast_ref::<SimulationField>()
         {
-            **text = value.to_string();
+            **text = format_value(name, **value);
         }
     }
 }
+
+/// `value` of the field named `name`, rounded to the precision of the field.
+fn format_value(name: &str, value: f32) -> String {
+    field_metadata(name).map_or_else(|| format!("{value:.2}"), |metadata| metadata.format(value))
+}
+
+/// Keeps the sliders in sync with values and ranges changed outside of the UI.
+fn update_slider_values(
+    cfg: Res<SimulationConfig>,
+    sliders: Query<(Entity, &SliderValue, &SliderRange, &Name)>,
+    mut commands: Commands,
+) {
+    for (slider_ent, slider_value, slider_range, name) in sliders.iter() {
+        if let Ok(reflect_struct) = cfg.reflect_ref().as_struct()
+            && let Some(field) = reflect_struct.field(name)
+            && let Some(field) = field.try_downcast_ref::<SimulationField>()
+        {
+            if slider_value.0 != **field {
+                commands.entity(slider_ent).insert(SliderValue(**field));
+            }
+
+            if slider_range.start() != field.min() || slider_range.end() != field.max() {
+                commands
+                    .entity(slider_ent)
+                    .insert(SliderRange::new(field.min(), field.max()));
+            }
+        }
+    }
+}
+
+fn update_clock_text(clock: Res<SimulationClock>, mut texts: Query<&mut Text, With<ClockText>>) {
+    for mut text in texts.iter_mut() {
+        **text = clock.to_string();
+    }
+}
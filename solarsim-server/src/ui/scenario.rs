@@ -0,0 +1,111 @@
+use bevy::{
+    prelude::*,
+    ui_widgets::{Activate, observe},
+};
+
+use crate::{
+    simulation::{
+        ActiveScenario, ChangeSource, InputRecorder, Scenario, SimulationClock, SimulationConfig,
+    },
+    ui::widgets::button,
+};
+
+#[derive(Component)]
+struct ScenarioText;
+
+/// Outcome of the last save or load.
+#[derive(Component)]
+struct ScenarioStatusText;
+
+/// Row with the active scenario and buttons to save or reload it.
+pub(super) fn scenario_controls(active: &ActiveScenario) -> impl Bundle {
+    (
+        Node {
+            align_items: AlignItems::Center,
+            column_gap: px(8),
+            ..default()
+        },
+        children![
+            (
+                ScenarioText,
+                Text::new(scenario_label(active)),
+                TextFont::from_font_size(16.0),
+            ),
+            (
+                button("Save"),
+                observe(
+                    |_: On<Activate>,
+                     active: Res<ActiveScenario>,
+                     cfg: Res<SimulationConfig>,
+                     mut texts: Query<&mut Text, With<ScenarioStatusText>>| {
+                        let scenario = Scenario::new(active.name.clone(), cfg.clone());
+                        let status = match scenario.save_named() {
+                            Ok(path) => format!("Saved to {}", path.display()),
+                            Err(error) => error.to_string(),
+                        };
+
+                        for mut text in texts.iter_mut() {
+                            **text = status.clone();
+                        }
+                    },
+                ),
+            ),
+            (
+                button("Load"),
+                observe(
+                    |_: On<Activate>,
+                     active: Res<ActiveScenario>,
+                     mut cfg: ResMut<SimulationConfig>,
+                     clock: Res<SimulationClock>,
+                     recorder: Option<ResMut<InputRecorder>>,
+                     mut texts: Query<&mut Text, With<ScenarioStatusText>>| {
+                        let status = match Scenario::load_named(&active.name) {
+                            Ok(scenario) => {
+                                if let Some(mut recorder) = recorder {
+                                    recorder.record_config(
+                                        clock.steps,
+                                        ChangeSource::Scenario,
+                                        &scenario.config,
+                                    );
+                                }
+
+                                *cfg = scenario.config;
+                                format!("Loaded {}", active.name)
+                            }
+                            Err(error) => error.to_string(),
+                        };
+
+                        for mut text in texts.iter_mut() {
+                            **text = status.clone();
+                        }
+                    },
+                ),
+            ),
+            (
+                ScenarioStatusText,
+                Text::default(),
+                TextFont::from_font_size(14.0),
+            ),
+        ],
+    )
+}
+
+fn scenario_label(active: &ActiveScenario) -> String {
+    format!("Scenario: {}", active.name)
+}
+
+fn update_scenario_text(
+    active: Res<ActiveScenario>,
+    mut texts: Query<&mut Text, With<ScenarioText>>,
+) {
+    for mut text in texts.iter_mut() {
+        **text = scenario_label(&active);
+    }
+}
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        update_scenario_text.run_if(resource_changed::<ActiveScenario>),
+    );
+}
@@ -0,0 +1,53 @@
+use bevy::{
+    input_focus::tab_navigation::TabIndex,
+    picking::hover::Hovered,
+    prelude::*,
+    ui::{Checked, Pressed},
+    ui_widgets::Button,
+};
+
+const BUTTON_BACKGROUND: Color = Color::srgb(0.05, 0.05, 0.05);
+const BUTTON_CHECKED: Color = Color::srgb(0.961, 0.659, 0.0);
+
+pub fn button(label: impl Into<String>) -> impl Bundle {
+    (
+        Node {
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            padding: UiRect::axes(px(12), px(4)),
+            ..default()
+        },
+        Button,
+        Hovered::default(),
+        TabIndex(0),
+        BackgroundColor(BUTTON_BACKGROUND),
+        BorderRadius::all(px(4)),
+        children![(Text::new(label), TextFont::from_font_size(14.0))],
+    )
+}
+
+fn button_color(checked: bool, hovered: bool, pressed: bool) -> Color {
+    let color = match checked {
+        true => BUTTON_CHECKED,
+        false => BUTTON_BACKGROUND,
+    };
+
+    match (hovered, pressed) {
+        (_, true) => color.lighter(0.15),
+        (true, false) => color.lighter(0.3),
+        (false, false) => color,
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub(super) fn update_button_style(
+    mut buttons: Query<(&Hovered, Has<Pressed>, Has<Checked>, &mut BackgroundColor), With<Button>>,
+) {
+    for (hovered, pressed, checked, mut background) in buttons.iter_mut() {
+        background.set_if_neq(BackgroundColor(button_color(checked, hovered.0, pressed)));
+    }
+}
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(Update, update_button_style);
+}
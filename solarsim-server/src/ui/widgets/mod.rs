@@ -3,8 +3,10 @@ use bevy::{
     ui_widgets::{Slider, SliderValue},
 };
 
+mod button;
 mod slider;
 
+pub use button::*;
 pub use slider::*;
 
 use crate::simulation::{SimulationConfig, SimulationField};
@@ -25,7 +27,7 @@ fn update_widget_values(
 }
 
 pub fn plugin(app: &mut App) {
-    app.add_plugins(slider::plugin);
+    app.add_plugins((button::plugin, slider::plugin));
 
     app.add_systems(
         Update,
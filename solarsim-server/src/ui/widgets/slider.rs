@@ -11,7 +11,7 @@ use bevy::{
 const SLIDER_TRACK: Color = Color::srgb(0.05, 0.05, 0.05);
 const SLIDER_THUMB: Color = Color::srgb(0.961, 0.659, 0.0);
 
-pub fn slider(min: f32, max: f32, value: f32) -> impl Bundle {
+pub fn slider(min: f32, max: f32, value: f32, precision: i32) -> impl Bundle {
     (
         Node {
             display: Display::Flex,
@@ -29,7 +29,7 @@ pub fn slider(min: f32, max: f32, value: f32) -> impl Bundle {
         },
         SliderValue(value),
         SliderRange::new(min, max),
-        SliderPrecision(2),
+        SliderPrecision(precision),
         TabIndex(0),
         Children::spawn((
             // Slider background rail
@@ -82,6 +82,7 @@ fn thumb_color(hovered: bool) -> Color {
     }
 }
 
+#[allow(clippy::type_complexity)]
 pub(super) fn update_slider_style(
     sliders: Query<
         (
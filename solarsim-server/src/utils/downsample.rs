@@ -0,0 +1,84 @@
+/// Reduces `points` to at most `max_points` with the Largest-Triangle-Three-Buckets algorithm.
+///
+/// The first and last points are kept, the points between them are split into buckets and the
+/// point of each bucket forming the largest triangle with its neighbours is kept, which preserves
+/// the peaks and shape of the series. Points must be sorted by `x`. Fewer than 3 points can't
+/// be downsampled, every point is returned then.
+pub fn lttb(points: &[(f64, f32)], max_points: usize) -> Vec<(f64, f32)> {
+    if max_points < 3 || points.len() <= max_points {
+        return points.to_vec();
+    }
+
+    let bucket_size = (points.len() - 2) as f64 / (max_points - 2) as f64;
+    let bucket = |index: usize| {
+        let start = (index as f64 * bucket_size) as usize + 1;
+        let end = ((index + 1) as f64 * bucket_size) as usize + 1;
+
+        start..end.min(points.len() - 1)
+    };
+    let mut sampled = Vec::with_capacity(max_points);
+    let mut selected = points[0];
+
+    sampled.push(selected);
+
+    for index in 0..max_points - 2 {
+        // The third corner of the triangles is the average of the next bucket.
+        let next = match bucket(index + 1) {
+            range if range.is_empty() => &points[points.len() - 1..],
+            range => &points[range],
+        };
+        let (next_x, next_y) = next.iter().fold((0.0, 0.0), |(x, y), point| {
+            (x + point.0, y + f64::from(point.1))
+        });
+        let (next_x, next_y) = (next_x / next.len() as f64, next_y / next.len() as f64);
+        let (selected_x, selected_y) = (selected.0, f64::from(selected.1));
+        let area = |point: &(f64, f32)| {
+            ((selected_x - next_x) * (f64::from(point.1) - selected_y)
+                - (selected_x - point.0) * (next_y - selected_y))
+                .abs()
+        };
+
+        if let Some(point) = points[bucket(index)]
+            .iter()
+            .max_by(|a, b| area(a).total_cmp(&area(b)))
+        {
+            selected = *point;
+            sampled.push(selected);
+        }
+    }
+
+    sampled.push(points[points.len() - 1]);
+    sampled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_series_are_kept() {
+        let points = [(0.0, 1.0), (1.0, 2.0), (2.0, 3.0)];
+
+        assert_eq!(lttb(&points, 5), points);
+        assert_eq!(lttb(&points, 2), points);
+    }
+
+    #[test]
+    fn keeps_the_ends_and_the_peaks() {
+        let mut points = (0..1000)
+            .map(|index| (f64::from(index), 0.0))
+            .collect::<Vec<_>>();
+
+        points[250].1 = 10.0;
+        points[750].1 = -10.0;
+
+        let sampled = lttb(&points, 10);
+
+        assert_eq!(sampled.len(), 10);
+        assert_eq!(sampled.first(), points.first());
+        assert_eq!(sampled.last(), points.last());
+        assert!(sampled.contains(&(250.0, 10.0)));
+        assert!(sampled.contains(&(750.0, -10.0)));
+        assert!(sampled.is_sorted_by(|a, b| a.0 < b.0));
+    }
+}
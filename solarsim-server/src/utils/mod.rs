@@ -1,5 +1,15 @@
+mod downsample;
+#[cfg(feature = "gui")]
 mod maybe;
+mod parallel;
+mod rng;
+#[cfg(feature = "gui")]
 mod strings;
 
+pub use downsample::*;
+#[cfg(feature = "gui")]
 pub use maybe::*;
+pub use parallel::*;
+pub use rng::*;
+#[cfg(feature = "gui")]
 pub use strings::*;